@@ -0,0 +1,353 @@
+use std::{
+    collections::HashMap,
+    env,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use lounge_parser::{
+    filters::LessonFilters, get_schedules, get_teacher_schedules, ical, schedules::DayItem,
+    timezone,
+};
+use tiny_http::{Header, Request, Response, Server};
+use tokio::runtime::Runtime;
+
+struct FeedSettings {
+    weeks_back: u64,
+    weeks_ahead: u64,
+    ttl: Duration,
+    cache_entries: usize,
+}
+
+/// Расписание с сайта ИБИ, одно на группу или преподавателя, какие бы ни были фильтры
+struct CachedSchedule {
+    days: Vec<DayItem>,
+    date_from: NaiveDate,
+    fetched_at: Instant,
+}
+
+struct CachedFeed {
+    body: String,
+    etag: String,
+    stamp: DateTime<Utc>,
+    /// `fetched_at` расписания, из которого собрана лента
+    built_from: Instant,
+}
+
+/// Ограниченный кэш: при переполнении выбрасывается запись, которую дольше всех не запрашивали
+struct Cache<T> {
+    entries: HashMap<String, (Instant, T)>,
+    max_entries: usize,
+}
+
+impl<T> Cache<T> {
+    fn new(max_entries: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            max_entries: max_entries.max(1),
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<&T> {
+        let (used_at, value) = self.entries.get_mut(key)?;
+        *used_at = Instant::now();
+        Some(value)
+    }
+
+    fn insert(&mut self, key: String, value: T) {
+        while self.entries.len() >= self.max_entries && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (used_at, _))| *used_at)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(oldest) => self.entries.remove(&oldest),
+                None => break,
+            };
+        }
+        self.entries.insert(key, (Instant::now(), value));
+    }
+}
+
+struct FeedCache {
+    schedules: Cache<CachedSchedule>,
+    feeds: Cache<CachedFeed>,
+}
+
+enum FeedOwner {
+    Group(String),
+    Teacher(String),
+}
+
+impl FeedOwner {
+    fn key(&self) -> String {
+        match self {
+            FeedOwner::Group(id) => format!("group/{}", id),
+            FeedOwner::Teacher(id) => format!("teacher/{}", id),
+        }
+    }
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    match env::var(name) {
+        Ok(val) => val.parse::<T>().unwrap_or(default),
+        Err(_e) => default,
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut result: Vec<u8> = vec![];
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => result.push(b' '),
+            b'%' if index + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        result.push(byte);
+                        index += 2;
+                    }
+                    Err(_) => result.push(b'%'),
+                }
+            }
+            byte => result.push(byte),
+        }
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&result).to_string()
+}
+
+// ?hide=Физкультура&hide_type=lecture&subgroup=1
+fn parse_filters(url: &str) -> LessonFilters {
+    let query = match url.split_once('?') {
+        Some((_, query)) => query,
+        None => return LessonFilters::default(),
+    };
+
+    let (mut hidden_subjects, mut hidden_types, mut subgroup) = (vec![], vec![], None);
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(value);
+
+        match key {
+            "hide" => hidden_subjects.push(value),
+            "hide_type" => hidden_types.push(value),
+            "subgroup" => subgroup = value.parse::<u8>().ok(),
+            _ => (),
+        }
+    }
+
+    LessonFilters::new(hidden_subjects, hidden_types, subgroup)
+}
+
+// Одинаковые фильтры в любом порядке и с лишними параметрами дают одну ленту
+fn filters_key(filters: &LessonFilters) -> String {
+    let sorted = |values: &[String]| {
+        let mut values: Vec<String> = values
+            .iter()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect();
+        values.sort();
+        values.dedup();
+        values.join("\u{1f}")
+    };
+
+    format!(
+        "{}\u{1e}{}\u{1e}{}",
+        sorted(filters.hidden_subjects()),
+        sorted(filters.hidden_types()),
+        filters
+            .subgroup()
+            .map(|subgroup| subgroup.to_string())
+            .unwrap_or_default()
+    )
+}
+
+// /group/<id>.ics, /teacher/<id>.ics
+fn parse_route(url: &str) -> Option<FeedOwner> {
+    let path = url.split('?').next().unwrap_or("");
+    let mut parts = path.trim_matches('/').split('/');
+    let (kind, id) = (parts.next()?, parts.next()?.strip_suffix(".ics")?);
+
+    if parts.next().is_some() || id.is_empty() {
+        return None;
+    }
+
+    match kind {
+        "group" => Some(FeedOwner::Group(id.to_string())),
+        "teacher" => Some(FeedOwner::Teacher(id.to_string())),
+        _ => None,
+    }
+}
+
+fn fetch_schedule(
+    rt: &Runtime,
+    owner: &FeedOwner,
+    settings: &FeedSettings,
+) -> Result<CachedSchedule, String> {
+    let today = timezone::today();
+    let date_from = today
+        .checked_sub_days(Days::new(settings.weeks_back * 7))
+        .unwrap();
+    let date_to = today
+        .checked_add_days(Days::new(settings.weeks_ahead * 7))
+        .unwrap();
+    let (date_from_formatted, date_to_formatted) = (
+        date_from.format("%d.%m.%Y").to_string(),
+        date_to.format("%d.%m.%Y").to_string(),
+    );
+
+    let days = match owner {
+        FeedOwner::Group(id) => {
+            rt.block_on(get_schedules(&date_from_formatted, &date_to_formatted, id))
+        }
+        FeedOwner::Teacher(id) => rt.block_on(get_teacher_schedules(
+            &date_from_formatted,
+            &date_to_formatted,
+            id,
+        )),
+    }?;
+
+    Ok(CachedSchedule {
+        days,
+        date_from,
+        fetched_at: Instant::now(),
+    })
+}
+
+fn build_feed(
+    name: &str,
+    schedule: &CachedSchedule,
+    filters: &LessonFilters,
+    previous: Option<&CachedFeed>,
+) -> CachedFeed {
+    let mut days = schedule.days.clone();
+    filters.apply(&mut days);
+
+    // DTSTAMP меняем только если поменялось само расписание, иначе ETag прыгал бы на каждом обновлении
+    if let Some(previous) = previous {
+        let body = ical::schedules_to_ical(&days, schedule.date_from, name, &previous.stamp);
+        if body == previous.body {
+            return CachedFeed {
+                body,
+                etag: previous.etag.clone(),
+                stamp: previous.stamp,
+                built_from: schedule.fetched_at,
+            };
+        }
+    }
+
+    let stamp = Utc::now();
+    let body = ical::schedules_to_ical(&days, schedule.date_from, name, &stamp);
+    CachedFeed {
+        etag: format!("\"{:016x}\"", ical::content_hash(&body)),
+        body,
+        stamp,
+        built_from: schedule.fetched_at,
+    }
+}
+
+fn header(field: &str, value: &str) -> Header {
+    Header::from_bytes(field.as_bytes(), value.as_bytes()).unwrap()
+}
+
+fn respond_text(request: Request, status: u16, text: &str) {
+    let _ = request.respond(
+        Response::from_string(text)
+            .with_status_code(status)
+            .with_header(header("Content-Type", "text/plain; charset=utf-8")),
+    );
+}
+
+fn handle_request(request: Request, rt: &Runtime, cache: &mut FeedCache, settings: &FeedSettings) {
+    let owner = match parse_route(request.url()) {
+        Some(owner) => owner,
+        None => return respond_text(request, 404, "Not found"),
+    };
+    let name = match &owner {
+        FeedOwner::Group(id) => format!("IBI group {}", id),
+        FeedOwner::Teacher(id) => format!("IBI teacher {}", id),
+    };
+    let filters = parse_filters(request.url());
+    // Сайт ИБИ запрашиваем один раз на группу, а фильтры применяем к копии из кэша
+    let owner_key = owner.key();
+    let feed_key = format!("{}?{}", owner_key, filters_key(&filters));
+
+    let is_stale = match cache.schedules.get(&owner_key) {
+        Some(cached) => cached.fetched_at.elapsed() > settings.ttl,
+        None => true,
+    };
+    if is_stale {
+        match fetch_schedule(rt, &owner, settings) {
+            Ok(schedule) => cache.schedules.insert(owner_key.clone(), schedule),
+            Err(err) => {
+                // Отдаём устаревшую копию, если сайт ИБИ недоступен
+                if cache.schedules.get(&owner_key).is_none() {
+                    return respond_text(request, 502, &err);
+                }
+            }
+        }
+    }
+
+    let schedule = cache.schedules.get(&owner_key).unwrap();
+    let previous = cache.feeds.get(&feed_key);
+    if previous.is_none_or(|feed| feed.built_from != schedule.fetched_at) {
+        let feed = build_feed(&name, schedule, &filters, previous);
+        cache.feeds.insert(feed_key.clone(), feed);
+    }
+
+    let feed = cache.feeds.get(&feed_key).unwrap();
+    let max_age = format!("max-age={}", settings.ttl.as_secs());
+    let if_none_match = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("If-None-Match"))
+        .map(|h| h.value.as_str().to_string());
+
+    match if_none_match {
+        Some(tag) if tag.split(',').any(|t| t.trim() == feed.etag) => {
+            let _ = request.respond(
+                Response::empty(304)
+                    .with_header(header("ETag", &feed.etag))
+                    .with_header(header("Cache-Control", &max_age)),
+            );
+        }
+        _ => {
+            let _ = request.respond(
+                Response::from_string(feed.body.clone())
+                    .with_header(header("Content-Type", "text/calendar; charset=utf-8"))
+                    .with_header(header("ETag", &feed.etag))
+                    .with_header(header("Cache-Control", &max_age)),
+            );
+        }
+    }
+}
+
+fn main() {
+    let address = env::var("LOUNGE_FEED_ADDR").unwrap_or("127.0.0.1:8080".to_string());
+    let settings = FeedSettings {
+        weeks_back: env_or("LOUNGE_FEED_WEEKS_BACK", 2),
+        weeks_ahead: env_or("LOUNGE_FEED_WEEKS_AHEAD", 6),
+        ttl: Duration::from_secs(env_or("LOUNGE_FEED_TTL", 900)),
+        cache_entries: env_or("LOUNGE_FEED_CACHE_ENTRIES", 64),
+    };
+
+    let server = Server::http(&address).unwrap();
+    let rt = Runtime::new().unwrap();
+    let mut cache = FeedCache {
+        schedules: Cache::new(settings.cache_entries),
+        feeds: Cache::new(settings.cache_entries),
+    };
+
+    println!("Serving iCal feeds on http://{}/group/<id>.ics", address);
+
+    for request in server.incoming_requests() {
+        handle_request(request, &rt, &mut cache, &settings);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{
+    grades::{GradeItem, GradeType, disciplines, normalize_name},
+    stats::csv_field,
+};
+
+pub struct SupplementEntry<'a> {
+    /// Номер семестра итогового контроля, с 1
+    pub semester: usize,
+    pub item: &'a GradeItem,
+}
+
+/// Итоговые оценки в разбивке, как в приложении к диплому
+pub struct DiplomaSupplement<'a> {
+    pub disciplines: Vec<SupplementEntry<'a>>,
+    pub course_works: Vec<SupplementEntry<'a>>,
+    pub gov_exams: Vec<SupplementEntry<'a>>,
+}
+
+pub fn diploma_supplement(semesters: &[Vec<GradeItem>; 8]) -> DiplomaSupplement<'_> {
+    let mut result = DiplomaSupplement {
+        disciplines: vec![],
+        course_works: vec![],
+        gov_exams: vec![],
+    };
+    let mut keys: Vec<String> = vec![];
+
+    for discipline in disciplines(semesters).iter().flatten() {
+        let entry = SupplementEntry {
+            semester: discipline.semester,
+            item: discipline.final_item(),
+        };
+
+        match discipline.r#type() {
+            GradeType::OfflineCourseWork | GradeType::OnlineCourseWork => {
+                result.course_works.push(entry)
+            }
+            GradeType::GovExam => result.gov_exams.push(entry),
+            _ => {
+                // Дисциплина на несколько семестров: в приложение идёт последний контроль
+                let key = normalize_name(discipline.name());
+                match keys.iter().position(|other| *other == key) {
+                    Some(position) => result.disciplines[position] = entry,
+                    None => {
+                        keys.push(key);
+                        result.disciplines.push(entry);
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn markdown_field(text: &str) -> String {
+    text.trim().replace('|', "\\|")
+}
+
+fn markdown_section(out: &mut String, title: &str, entries: &[SupplementEntry]) {
+    out.push_str(&format!("\n## {}\n\n", title));
+
+    if entries.is_empty() {
+        out.push_str("—\n");
+        return;
+    }
+
+    out.push_str("| № | Наименование | Форма контроля | Семестр | Оценка |\n");
+    out.push_str("|---|---|---|---|---|\n");
+    for (index, entry) in entries.iter().enumerate() {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            index + 1,
+            markdown_field(&entry.item.name),
+            entry.item.r#type.to_label(),
+            entry.semester,
+            entry.item.grade.to_label()
+        ));
+    }
+}
+
+impl DiplomaSupplement<'_> {
+    fn sections(&self) -> [(&'static str, &[SupplementEntry<'_>]); 3] {
+        [
+            ("Дисциплины", &self.disciplines),
+            ("Курсовые работы", &self.course_works),
+            ("Государственная итоговая аттестация", &self.gov_exams),
+        ]
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Приложение к диплому\n");
+
+        for (title, entries) in self.sections() {
+            markdown_section(&mut out, title, entries);
+        }
+
+        out
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("Раздел,Наименование,Форма контроля,Семестр,Оценка\n");
+
+        for (title, entries) in self.sections() {
+            for entry in entries {
+                out.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    csv_field(title),
+                    csv_field(entry.item.name.trim()),
+                    entry.item.r#type.to_label(),
+                    entry.semester,
+                    entry.item.grade.to_label()
+                ));
+            }
+        }
+
+        out
+    }
+}
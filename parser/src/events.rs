@@ -0,0 +1,220 @@
+use chrono::{Datelike, Days, NaiveDate, NaiveTime};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::schedules::{
+    BellSlot, DayItem, LessonItem,
+    additional::{AdditionalLessonInfo, LessonType},
+};
+
+const WEEK_DAYS: [&str; 7] = ["Пн", "Вт", "Ср", "Чт", "Пт", "Сб", "Вс"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum Recurrence {
+    #[default]
+    Once,
+    Daily,
+    Weekly,
+    BiWeekly,
+}
+
+impl Recurrence {
+    pub fn to_text(self) -> &'static str {
+        match self {
+            Self::Once => "once",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::BiWeekly => "bi_weekly",
+        }
+    }
+}
+
+/// Личное событие пользователя: подготовка, консультация у преподавателя, смена на работе
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PersonalEvent {
+    pub title: String,
+    /// %d.%m.%Y, первое повторение
+    pub date: String,
+    /// %H:%M
+    pub time_start: String,
+    /// %H:%M
+    pub time_end: String,
+    #[serde(default)]
+    pub place: Option<String>,
+    #[serde(default)]
+    pub recurrence: Recurrence,
+    /// %d.%m.%Y, включительно
+    #[serde(default)]
+    pub until: Option<String>,
+    /// Событие из подключённого .ics, а не созданное вручную
+    #[serde(skip)]
+    pub external: bool,
+    /// Пропущенные повторения (EXDATE и перенесённые через RECURRENCE-ID), только из .ics
+    #[serde(skip)]
+    pub exceptions: Vec<NaiveDate>,
+}
+
+impl PersonalEvent {
+    pub fn first_date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(self.date.trim(), "%d.%m.%Y").ok()
+    }
+
+    pub fn until_date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(self.until.as_ref()?.trim(), "%d.%m.%Y").ok()
+    }
+
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        let first = match self.first_date() {
+            Some(first) => first,
+            None => return false,
+        };
+
+        if date < first
+            || self.until_date().is_some_and(|until| date > until)
+            || self.exceptions.contains(&date)
+        {
+            return false;
+        }
+
+        let days = (date - first).num_days();
+        match self.recurrence {
+            Recurrence::Once => days == 0,
+            Recurrence::Daily => true,
+            Recurrence::Weekly => days % 7 == 0,
+            Recurrence::BiWeekly => days % 14 == 0,
+        }
+    }
+
+    pub fn to_lesson(&self, slots: &[BellSlot]) -> LessonItem {
+        LessonItem {
+            time_start: self.time_start.trim().to_string(),
+            time_end: self.time_end.trim().to_string(),
+            text: self.title.trim().to_string(),
+            additional: AdditionalLessonInfo {
+                teacher_name: None,
+                classroom: self.place.clone().filter(|place| !place.trim().is_empty()),
+                online: false,
+                groups: None,
+                r#type: if self.external {
+                    LessonType::External
+                } else {
+                    LessonType::Personal
+                },
+            },
+            urls: vec![],
+            slot: event_slot(slots, &self.time_start),
+        }
+    }
+}
+
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(text.trim(), "%H:%M").ok()
+}
+
+// Пара, в которую попадает начало события, а если оно вне сетки звонков — ближайшая предыдущая
+fn event_slot(slots: &[BellSlot], time_start: &str) -> usize {
+    let time_start = match parse_time(time_start) {
+        Some(time) => time,
+        None => return 0,
+    };
+
+    slots
+        .iter()
+        .rposition(|slot| parse_time(&slot.time_start).is_some_and(|start| start <= time_start))
+        .unwrap_or(0)
+}
+
+/// Пересекаются ли занятия по времени (касание концами не считается)
+pub fn lessons_overlap(a: &LessonItem, b: &LessonItem) -> bool {
+    match (
+        parse_time(&a.time_start),
+        parse_time(&a.time_end),
+        parse_time(&b.time_start),
+        parse_time(&b.time_end),
+    ) {
+        (Some(a_start), Some(a_end), Some(b_start), Some(b_end)) => {
+            a_start < b_end && b_start < a_end
+        }
+        _ => false,
+    }
+}
+
+/// Для каждого занятия дня: пересекается ли оно с занятием другого происхождения
+/// (личное или внешнее событие с парой). Накладки внутри расписания ИБИ — это подгруппы, их не отмечаем.
+pub fn day_conflicts(day: &DayItem) -> Vec<bool> {
+    day.lessons
+        .iter()
+        .map(|lesson| {
+            day.lessons.iter().any(|other| {
+                !std::ptr::eq(lesson, other)
+                    && lesson.additional.r#type.is_official()
+                        != other.additional.r#type.is_official()
+                    && lessons_overlap(lesson, other)
+            })
+        })
+        .collect()
+}
+
+/// Добавляет события в расписание за период; дни без пар создаются
+pub fn merge_events(
+    days: &mut Vec<DayItem>,
+    events: &[PersonalEvent],
+    date_from: NaiveDate,
+    date_to: NaiveDate,
+) {
+    if events.is_empty() {
+        return;
+    }
+
+    let slots = days
+        .iter()
+        .map(|day| day.slots.clone())
+        .max_by_key(|slots| slots.len())
+        .unwrap_or_default();
+
+    let mut date = date_from;
+    while date <= date_to {
+        let occurring: Vec<&PersonalEvent> = events
+            .iter()
+            .filter(|event| event.occurs_on(date))
+            .collect();
+
+        if !occurring.is_empty() {
+            let index = match days
+                .iter()
+                .position(|day| day.date(date_from) == Some(date))
+            {
+                Some(index) => index,
+                None => {
+                    let index = days
+                        .iter()
+                        .position(|day| day.date(date_from).is_some_and(|day| day > date))
+                        .unwrap_or(days.len());
+                    days.insert(
+                        index,
+                        DayItem {
+                            day: date.format("%d").to_string(),
+                            month: date.format("%m").to_string(),
+                            week_day: WEEK_DAYS[date.weekday().num_days_from_monday() as usize]
+                                .to_string(),
+                            lessons: vec![],
+                            slots: slots.clone(),
+                        },
+                    );
+                    index
+                }
+            };
+
+            let day = &mut days[index];
+            for event in occurring {
+                day.lessons.push(event.to_lesson(&day.slots));
+            }
+            day.lessons
+                .sort_by_key(|lesson| parse_time(&lesson.time_start));
+        }
+
+        date = match date.checked_add_days(Days::new(1)) {
+            Some(date) => date,
+            None => break,
+        };
+    }
+}
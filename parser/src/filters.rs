@@ -0,0 +1,122 @@
+use regex::Regex;
+use serde_derive::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::schedules::{DayItem, LessonItem};
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct LessonFilters {
+    /// Название предмета или регулярное выражение (без учёта регистра)
+    #[serde(default)]
+    hidden_subjects: Vec<String>,
+    /// Ключи `LessonType::to_text`
+    #[serde(default)]
+    hidden_types: Vec<String>,
+    /// Оставить только занятия этой подгруппы (и общие для всей группы)
+    #[serde(default)]
+    subgroup: Option<u8>,
+    /// `hidden_subjects`, скомпилированные при первой проверке. Поля закрыты,
+    /// поэтому изменить их можно только вместе с кэшем, через `new`
+    #[serde(skip)]
+    patterns: OnceLock<Vec<SubjectPattern>>,
+}
+
+#[derive(Clone)]
+enum SubjectPattern {
+    Regex(Regex),
+    /// Не регулярное выражение: ищем как подстроку, в нижнем регистре
+    Text(String),
+}
+
+impl SubjectPattern {
+    fn new(pattern: &str) -> Option<Self> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return None;
+        }
+
+        Some(match Regex::new(&format!("(?i){}", pattern)) {
+            Ok(regex) => Self::Regex(regex),
+            Err(_) => Self::Text(pattern.to_lowercase()),
+        })
+    }
+
+    fn is_match(&self, subject: &str) -> bool {
+        match self {
+            Self::Regex(regex) => regex.is_match(subject),
+            Self::Text(text) => subject.to_lowercase().contains(text),
+        }
+    }
+}
+
+impl LessonFilters {
+    pub fn new(
+        hidden_subjects: Vec<String>,
+        hidden_types: Vec<String>,
+        subgroup: Option<u8>,
+    ) -> Self {
+        Self {
+            hidden_subjects,
+            hidden_types,
+            subgroup,
+            patterns: OnceLock::new(),
+        }
+    }
+
+    pub fn hidden_subjects(&self) -> &[String] {
+        &self.hidden_subjects
+    }
+
+    pub fn hidden_types(&self) -> &[String] {
+        &self.hidden_types
+    }
+
+    pub fn subgroup(&self) -> Option<u8> {
+        self.subgroup
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hidden_subjects.is_empty() && self.hidden_types.is_empty() && self.subgroup.is_none()
+    }
+
+    pub fn is_hidden(&self, lesson: &LessonItem) -> bool {
+        if self
+            .hidden_types
+            .contains(&lesson.additional.r#type.to_text())
+        {
+            return true;
+        }
+        // Личные и внешние события пользователь добавил сам: предмет и подгруппа к ним не относятся
+        if !lesson.additional.r#type.is_official() {
+            return false;
+        }
+
+        let patterns = self.patterns.get_or_init(|| {
+            self.hidden_subjects
+                .iter()
+                .filter_map(|pattern| SubjectPattern::new(pattern))
+                .collect()
+        });
+        if patterns
+            .iter()
+            .any(|pattern| pattern.is_match(&lesson.text))
+        {
+            return true;
+        }
+
+        match (self.subgroup, lesson.additional.subgroup()) {
+            (Some(subgroup), Some(lesson_subgroup)) => subgroup != lesson_subgroup,
+            _ => false,
+        }
+    }
+
+    pub fn apply(&self, days: &mut [DayItem]) {
+        if self.is_empty() {
+            return;
+        }
+
+        for day in days {
+            day.lessons.retain(|lesson| !self.is_hidden(lesson));
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use chrono::NaiveTime;
+
+use crate::schedules::{DayItem, LessonItem};
+
+/// Перемены между парами (10 минут) окнами не считаются, обеденный перерыв — считается
+pub const MIN_GAP_MINUTES: i64 = 30;
+
+/// Окно между занятиями: выводится после занятия с индексом `after`
+pub struct Gap {
+    pub after: usize,
+    pub from: NaiveTime,
+    pub to: NaiveTime,
+}
+
+impl Gap {
+    pub fn minutes(&self) -> i64 {
+        (self.to - self.from).num_minutes()
+    }
+}
+
+pub struct DaySummary {
+    /// Пары ИБИ; параллельные занятия подгрупп считаются одной парой
+    pub pairs: usize,
+    pub first_start: Option<NaiveTime>,
+    pub last_end: Option<NaiveTime>,
+    pub contact_minutes: i64,
+}
+
+fn lesson_times(lesson: &LessonItem) -> Option<(NaiveTime, NaiveTime)> {
+    Some((
+        NaiveTime::parse_from_str(lesson.time_start.trim(), "%H:%M").ok()?,
+        NaiveTime::parse_from_str(lesson.time_end.trim(), "%H:%M").ok()?,
+    ))
+}
+
+/// Окна дня с учётом всех занятий, в том числе личных событий: занятое ими время уже не свободно
+pub fn day_gaps(day: &DayItem) -> Vec<Gap> {
+    let mut gaps: Vec<Gap> = vec![];
+    let mut busy_until: Option<NaiveTime> = None;
+
+    for (index, lesson) in day.lessons.iter().enumerate() {
+        let (start, end) = match lesson_times(lesson) {
+            Some(times) => times,
+            None => continue,
+        };
+
+        if let Some(busy_until) = busy_until
+            && (start - busy_until).num_minutes() >= MIN_GAP_MINUTES
+        {
+            gaps.push(Gap {
+                after: index - 1,
+                from: busy_until,
+                to: start,
+            });
+        }
+
+        busy_until = Some(busy_until.map_or(end, |busy_until| busy_until.max(end)));
+    }
+
+    gaps
+}
+
+/// Нагрузка дня только по занятиям из расписания ИБИ
+pub fn day_summary(day: &DayItem) -> DaySummary {
+    let mut times: Vec<(NaiveTime, NaiveTime)> = day
+        .lessons
+        .iter()
+        .filter(|lesson| lesson.additional.r#type.is_official())
+        .filter_map(lesson_times)
+        .collect();
+    times.sort();
+
+    let mut summary = DaySummary {
+        pairs: 0,
+        first_start: times.first().map(|(start, _)| *start),
+        last_end: times.iter().map(|(_, end)| *end).max(),
+        contact_minutes: 0,
+    };
+
+    // Пересекающиеся занятия (подгруппы) склеиваем, чтобы не считать часы дважды
+    let mut current: Option<(NaiveTime, NaiveTime)> = None;
+    for (start, end) in times {
+        match current {
+            Some((current_start, current_end)) if start < current_end => {
+                current = Some((current_start, current_end.max(end)));
+            }
+            _ => {
+                if let Some((current_start, current_end)) = current {
+                    summary.contact_minutes += (current_end - current_start).num_minutes();
+                }
+                summary.pairs += 1;
+                current = Some((start, end));
+            }
+        }
+    }
+    if let Some((current_start, current_end)) = current {
+        summary.contact_minutes += (current_end - current_start).num_minutes();
+    }
+
+    summary
+}
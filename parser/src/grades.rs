@@ -1,6 +1,8 @@
 use scraper::{Html, Selector};
 use std::vec;
 
+use crate::stats::csv_field;
+
 pub enum GradeType {
     SubjectReportWithGrade,
     SubjectReport,
@@ -82,6 +84,8 @@ pub struct GradeItem {
     pub name: String,
     pub r#type: GradeType,
     pub grade: GradeResult,
+    /// Оценка как она написана на сайте, на случай нераспознанных значений
+    pub raw: String,
 }
 
 pub fn parse_grade_table(html: String) -> [Vec<GradeItem>; 8] {
@@ -113,6 +117,16 @@ pub fn parse_grade_table(html: String) -> [Vec<GradeItem>; 8] {
             let discipline_name = data.nth(0).unwrap();
             let grade_type = data.next().unwrap();
             let grade_result = data.next().unwrap();
+            // Текст без разметки и &nbsp;
+            let raw = grade_result
+                .child_elements()
+                .next()
+                .unwrap_or(grade_result)
+                .text()
+                .collect::<String>()
+                .replace('\u{a0}', " ")
+                .trim()
+                .to_owned();
 
             result.push(GradeItem {
                 name: discipline_name
@@ -122,9 +136,8 @@ pub fn parse_grade_table(html: String) -> [Vec<GradeItem>; 8] {
                 r#type: GradeType::from_parsed(
                     &grade_type.inner_html().replace("&nbsp;", "").to_owned(),
                 ),
-                grade: GradeResult::from_parsed(
-                    &grade_result.child_elements().nth(0).unwrap().inner_html(),
-                ),
+                grade: GradeResult::from_parsed(&raw),
+                raw,
             })
         }
         semesters[index] = result;
@@ -132,3 +145,337 @@ pub fn parse_grade_table(html: String) -> [Vec<GradeItem>; 8] {
 
     semesters
 }
+
+impl GradeResult {
+    /// Числовая оценка; зачёты, неявки и недопуски в среднем не участвуют
+    pub fn value(&self) -> Option<u8> {
+        match self {
+            GradeResult::Two => Some(2),
+            GradeResult::Three => Some(3),
+            GradeResult::Four => Some(4),
+            GradeResult::Five => Some(5),
+            _ => None,
+        }
+    }
+}
+
+/// Сколько раз встретился результат `grade` у формы контроля `r#type` (ключи `to_string`)
+pub struct OutcomeCount {
+    pub r#type: String,
+    pub grade: String,
+    pub count: usize,
+}
+
+#[derive(Default)]
+pub struct SemesterStats {
+    pub average: Option<f64>,
+    /// Количество числовых оценок
+    pub graded: usize,
+    pub excellent: usize,
+    pub outcomes: Vec<OutcomeCount>,
+}
+
+impl SemesterStats {
+    /// Доля пятёрок среди числовых оценок, 0.0..=1.0
+    pub fn excellent_share(&self) -> Option<f64> {
+        if self.graded == 0 {
+            return None;
+        }
+        Some(self.excellent as f64 / self.graded as f64)
+    }
+
+    fn add(&mut self, item: &GradeItem) {
+        let (type_key, grade_key) = (item.r#type.to_string(), item.grade.to_string());
+        match self
+            .outcomes
+            .iter_mut()
+            .find(|outcome| outcome.r#type == type_key && outcome.grade == grade_key)
+        {
+            Some(outcome) => outcome.count += 1,
+            None => self.outcomes.push(OutcomeCount {
+                r#type: type_key.to_string(),
+                grade: grade_key.to_string(),
+                count: 1,
+            }),
+        }
+
+        if let Some(value) = item.grade.value() {
+            let sum = self.average.unwrap_or(0.0) * self.graded as f64 + value as f64;
+            self.graded += 1;
+            self.average = Some(sum / self.graded as f64);
+            if value == 5 {
+                self.excellent += 1;
+            }
+        }
+    }
+}
+
+pub struct GradesStats {
+    /// По одному на каждый из 8 семестров, пустые семестры без среднего
+    pub semesters: Vec<SemesterStats>,
+    pub overall: SemesterStats,
+    /// Изменение среднего между двумя последними семестрами с оценками
+    pub trend: Option<f64>,
+}
+
+pub fn grades_stats(semesters: &[Vec<GradeItem>; 8]) -> GradesStats {
+    let mut overall = SemesterStats::default();
+    let mut result: Vec<SemesterStats> = vec![];
+
+    for semester in semesters {
+        let mut stats = SemesterStats::default();
+        for item in semester {
+            stats.add(item);
+            overall.add(item);
+        }
+        result.push(stats);
+    }
+
+    let averages: Vec<f64> = result.iter().filter_map(|stats| stats.average).collect();
+    let trend = match averages.as_slice() {
+        [.., previous, last] => Some(last - previous),
+        _ => None,
+    };
+
+    GradesStats {
+        semesters: result,
+        overall,
+        trend,
+    }
+}
+
+impl GradeResult {
+    pub fn is_failing(&self) -> bool {
+        matches!(
+            self,
+            GradeResult::Failed
+                | GradeResult::Absence
+                | GradeResult::NotAdmitted
+                | GradeResult::Two
+        )
+    }
+
+    pub fn is_passing(&self) -> bool {
+        matches!(
+            self,
+            GradeResult::Passed | GradeResult::Three | GradeResult::Four | GradeResult::Five
+        )
+    }
+}
+
+/// Академическая задолженность: дисциплина, не сданная и после пересдач
+pub struct Debt<'a> {
+    /// Номер семестра, с 1
+    pub semester: usize,
+    pub item: &'a GradeItem,
+}
+
+/// Название без учёта регистра и лишних пробелов
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+pub struct Attempt<'a> {
+    /// Номер попытки, с 1
+    pub number: usize,
+    /// Позиция записи в таблице семестра
+    pub index: usize,
+    pub item: &'a GradeItem,
+}
+
+/// Все попытки сдать одну дисциплину в семестре: первая сдача и пересдачи
+pub struct Discipline<'a> {
+    /// Номер семестра, с 1
+    pub semester: usize,
+    pub attempts: Vec<Attempt<'a>>,
+}
+
+impl<'a> Discipline<'a> {
+    pub fn name(&self) -> &'a str {
+        &self.attempts[0].item.name
+    }
+
+    pub fn r#type(&self) -> &'a GradeType {
+        &self.attempts[0].item.r#type
+    }
+
+    /// Последняя попытка с выставленной оценкой; пересдача без оценки итог не меняет
+    pub fn final_item(&self) -> &'a GradeItem {
+        self.attempts
+            .iter()
+            .rev()
+            .find(|attempt| !matches!(attempt.item.grade, GradeResult::Unknown))
+            .unwrap_or(&self.attempts[self.attempts.len() - 1])
+            .item
+    }
+
+    pub fn final_grade(&self) -> &'a GradeResult {
+        &self.final_item().grade
+    }
+
+    pub fn is_retaken(&self) -> bool {
+        self.attempts.len() > 1
+    }
+}
+
+/// Группирует записи семестра по дисциплине. Форма контроля тоже входит в ключ:
+/// курсовая по предмету называется так же, как и сам предмет
+pub fn group_attempts(semester: usize, items: &[GradeItem]) -> Vec<Discipline<'_>> {
+    let mut keys: Vec<(String, &str)> = vec![];
+    let mut result: Vec<Discipline> = vec![];
+
+    for (index, item) in items.iter().enumerate() {
+        let key = (normalize_name(&item.name), item.r#type.to_string());
+        let position = match keys.iter().position(|other| *other == key) {
+            Some(position) => position,
+            None => {
+                keys.push(key);
+                result.push(Discipline {
+                    semester,
+                    attempts: vec![],
+                });
+                result.len() - 1
+            }
+        };
+
+        let discipline = &mut result[position];
+        discipline.attempts.push(Attempt {
+            number: discipline.attempts.len() + 1,
+            index,
+            item,
+        });
+    }
+
+    result
+}
+
+/// Одна задолженность на дисциплину в семестре: итог после всех пересдач не сдан.
+/// Семестры не смешиваются, у дисциплин вроде физкультуры в каждом семестре свой контроль
+pub fn academic_debts(semesters: &[Vec<GradeItem>; 8]) -> Vec<Debt<'_>> {
+    disciplines(semesters)
+        .iter()
+        .flatten()
+        .filter(|discipline| discipline.final_grade().is_failing())
+        .map(|discipline| Debt {
+            semester: discipline.semester,
+            item: discipline.final_item(),
+        })
+        .collect()
+}
+
+/// Дисциплины по семестрам, в порядке таблиц
+pub fn disciplines(semesters: &[Vec<GradeItem>; 8]) -> Vec<Vec<Discipline<'_>>> {
+    semesters
+        .iter()
+        .enumerate()
+        .map(|(index, items)| group_attempts(index + 1, items))
+        .collect()
+}
+
+impl GradeType {
+    /// Как форма контроля пишется в приложении к диплому
+    pub fn to_label(&self) -> &'static str {
+        match self {
+            GradeType::GovExam => "Государственный экзамен",
+            GradeType::Exam => "Экзамен",
+            GradeType::OfflineCourseWork | GradeType::OnlineCourseWork => "Курсовая работа",
+            GradeType::SubjectReport => "Зачёт",
+            GradeType::SubjectReportWithGrade => "Дифференцированный зачёт",
+            GradeType::Unknown => "—",
+        }
+    }
+}
+
+impl GradeResult {
+    pub fn to_label(&self) -> &'static str {
+        match self {
+            GradeResult::Failed => "не зачтено",
+            GradeResult::Passed => "зачтено",
+            GradeResult::Absence => "неявка",
+            GradeResult::NotAdmitted => "не допущен",
+            GradeResult::Two => "неудовлетворительно",
+            GradeResult::Three => "удовлетворительно",
+            GradeResult::Four => "хорошо",
+            GradeResult::Five => "отлично",
+            GradeResult::Unknown => "—",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Markdown => "md",
+        }
+    }
+}
+
+/// Все семестры одной таблицей: семестр, дисциплина, форма контроля, оценка, текст с сайта.
+/// Форма контроля и оценка во всех форматах подписаны одинаково, как в приложении к диплому
+pub fn export_grades(semesters: &[Vec<GradeItem>; 8], format: ExportFormat) -> String {
+    let rows: Vec<(usize, &GradeItem)> = semesters
+        .iter()
+        .enumerate()
+        .flat_map(|(index, semester)| semester.iter().map(move |item| (index + 1, item)))
+        .collect();
+
+    match format {
+        ExportFormat::Csv => {
+            let mut out = String::from("Семестр,Дисциплина,Форма контроля,Оценка,На сайте\n");
+            for (semester, item) in rows {
+                out.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    semester,
+                    csv_field(item.name.trim()),
+                    csv_field(item.r#type.to_label()),
+                    csv_field(item.grade.to_label()),
+                    csv_field(&item.raw)
+                ));
+            }
+            out
+        }
+        ExportFormat::Json => {
+            let rows: Vec<serde_json::Value> = rows
+                .iter()
+                .map(|(semester, item)| {
+                    serde_json::json!({
+                        "semester": semester,
+                        "discipline": item.name.trim(),
+                        "type": item.r#type.to_label(),
+                        "grade": item.grade.to_label(),
+                        "raw": item.raw,
+                    })
+                })
+                .collect();
+            serde_json::to_string_pretty(&rows).unwrap_or_default()
+        }
+        ExportFormat::Markdown => {
+            let mut out =
+                String::from("| Семестр | Дисциплина | Форма контроля | Оценка | На сайте |\n");
+            out.push_str("|---|---|---|---|---|\n");
+            for (semester, item) in rows {
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    semester,
+                    item.name.trim().replace('|', "\\|"),
+                    item.r#type.to_label(),
+                    item.grade.to_label(),
+                    item.raw.replace('|', "\\|")
+                ));
+            }
+            out
+        }
+    }
+}
@@ -0,0 +1,653 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::{
+    events::{PersonalEvent, Recurrence},
+    schedules::{DayItem, LessonItem},
+    timezone::TIMEZONE,
+};
+
+/// FNV-1a, стабилен между запусками и версиями компилятора (в отличие от DefaultHasher)
+pub fn content_hash(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// RFC 5545: строки длиннее 75 октетов переносятся, продолжение начинается с пробела
+fn push_line(out: &mut String, line: &str) {
+    let mut length = 0;
+    for ch in line.chars() {
+        if length + ch.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            length = 1;
+        }
+        out.push(ch);
+        length += ch.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn format_local(date: NaiveDate, time: NaiveTime) -> String {
+    NaiveDateTime::new(date, time)
+        .format("%Y%m%dT%H%M%S")
+        .to_string()
+}
+
+fn push_event(out: &mut String, date: NaiveDate, lesson: &LessonItem, stamp: &DateTime<Utc>) {
+    let (time_start, time_end) = match (
+        NaiveTime::parse_from_str(lesson.time_start.trim(), "%H:%M"),
+        NaiveTime::parse_from_str(lesson.time_end.trim(), "%H:%M"),
+    ) {
+        (Ok(time_start), Ok(time_end)) => (time_start, time_end),
+        _ => return,
+    };
+
+    // UID не зависит от аудитории, чтобы календарь обновил событие при переносе, а не создал новое.
+    // Подгруппа и преподаватель различают одинаковые занятия в одной паре
+    let uid = format!(
+        "{}-{}-{:016x}@lounge-tui",
+        date.format("%Y%m%d"),
+        time_start.format("%H%M"),
+        content_hash(&format!(
+            "{}\u{1f}{}\u{1f}{}",
+            lesson.text,
+            lesson.additional.groups.as_deref().unwrap_or_default(),
+            lesson
+                .additional
+                .teacher_name
+                .as_deref()
+                .unwrap_or_default()
+        ))
+    );
+
+    let label = lesson.additional.r#type.to_label();
+    let summary = if label.is_empty() {
+        lesson.text.clone()
+    } else {
+        format!("{} ({})", lesson.text, label)
+    };
+
+    let location = if lesson.additional.online {
+        "Онлайн".to_string()
+    } else {
+        lesson.additional.classroom.clone().unwrap_or_default()
+    };
+
+    let mut description: Vec<String> = vec![];
+    match &lesson.additional.teacher_name {
+        Some(teacher) if !teacher.is_empty() => description.push(teacher.clone()),
+        _ => (),
+    }
+    if let Some(groups) = &lesson.additional.groups {
+        description.push(groups.clone());
+    }
+    for url in &lesson.urls {
+        description.push(format!("{}: {}", url.text, url.url));
+    }
+
+    push_line(out, "BEGIN:VEVENT");
+    push_line(out, &format!("UID:{}", uid));
+    push_line(out, &format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+    push_line(
+        out,
+        &format!(
+            "DTSTART;TZID={}:{}",
+            TIMEZONE.name(),
+            format_local(date, time_start)
+        ),
+    );
+    push_line(
+        out,
+        &format!(
+            "DTEND;TZID={}:{}",
+            TIMEZONE.name(),
+            format_local(date, time_end)
+        ),
+    );
+    push_line(out, &format!("SUMMARY:{}", escape_text(&summary)));
+    if !location.is_empty() {
+        push_line(out, &format!("LOCATION:{}", escape_text(&location)));
+    }
+    if !description.is_empty() {
+        push_line(
+            out,
+            &format!("DESCRIPTION:{}", escape_text(&description.join("\n"))),
+        );
+    }
+    push_line(out, "END:VEVENT");
+}
+
+/// Собирает VCALENDAR из расписания; `date_from` нужен для восстановления года у `DayItem`
+pub fn schedules_to_ical(
+    days: &[DayItem],
+    date_from: NaiveDate,
+    calendar_name: &str,
+    stamp: &DateTime<Utc>,
+) -> String {
+    let mut out = String::new();
+
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//lounge-tui//IBI schedules//RU");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "METHOD:PUBLISH");
+    push_line(
+        &mut out,
+        &format!("X-WR-CALNAME:{}", escape_text(calendar_name)),
+    );
+    push_line(&mut out, &format!("X-WR-TIMEZONE:{}", TIMEZONE.name()));
+
+    // С 2014 года в Москве нет перехода на летнее время, поэтому достаточно одного STANDARD
+    push_line(&mut out, "BEGIN:VTIMEZONE");
+    push_line(&mut out, &format!("TZID:{}", TIMEZONE.name()));
+    push_line(&mut out, "BEGIN:STANDARD");
+    push_line(&mut out, "DTSTART:19700101T000000");
+    push_line(&mut out, "TZOFFSETFROM:+0300");
+    push_line(&mut out, "TZOFFSETTO:+0300");
+    push_line(&mut out, "TZNAME:MSK");
+    push_line(&mut out, "END:STANDARD");
+    push_line(&mut out, "END:VTIMEZONE");
+
+    for day in days {
+        if let Some(date) = day.date(date_from) {
+            for lesson in &day.lessons {
+                push_event(&mut out, date, lesson, stamp);
+            }
+        }
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+
+    out
+}
+
+// Обратное к escape_text
+fn unescape_text(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => (),
+        }
+    }
+
+    result
+}
+
+// DTSTART;TZID=Europe/Moscow:20251103T140000 -> московское время.
+// None для событий на весь день (VALUE=DATE): они накрыли бы все пары сразу.
+fn parse_date_time(params: &str, value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let date_time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(date_time.and_utc().with_timezone(&TIMEZONE).naive_local());
+    }
+
+    let date_time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let tz = params
+        .split(';')
+        .find_map(|param| param.strip_prefix("TZID="))
+        .and_then(|tzid| tzid.trim_matches('"').parse::<Tz>().ok());
+
+    match tz {
+        Some(tz) if tz != TIMEZONE => {
+            let local = tz.from_local_datetime(&date_time).earliest()?;
+            Some(local.with_timezone(&TIMEZONE).naive_local())
+        }
+        // Плавающее время и незнакомые TZID (например, виндовые) считаем московскими
+        _ => Some(date_time),
+    }
+}
+
+const RRULE_DAYS: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+// VALUE=DATE (20251103) или дата-время, как в DTSTART
+fn parse_date(params: &str, value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    if value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d").ok();
+    }
+    parse_date_time(params, value).map(|date_time| date_time.date())
+}
+
+/// Одна серия повторений: первая дата, шаг и последняя дата включительно
+struct Series {
+    first: NaiveDate,
+    recurrence: Recurrence,
+    until: Option<NaiveDate>,
+}
+
+// Поддерживаем только то, что выражается через Recurrence: FREQ=DAILY/WEEKLY, INTERVAL 1 или 2
+// для недель, BYDAY без номеров, UNTIL, COUNT. BYDAY раскладывается в серию на каждый день недели.
+// None для остального (MONTHLY, BYMONTHDAY, ...): такое событие лучше не показать, чем показать неверно
+fn parse_rrule(rule: &str, first: NaiveDate) -> Option<Vec<Series>> {
+    let mut freq = "";
+    let mut interval = 1;
+    let mut until: Option<NaiveDate> = None;
+    let mut count: Option<u64> = None;
+    let mut week_start = "MO";
+    let mut week_days: Vec<u32> = vec![];
+
+    for part in rule.trim().split(';').filter(|part| !part.is_empty()) {
+        match part.split_once('=')? {
+            ("FREQ", value) => freq = value,
+            ("INTERVAL", value) => interval = value.parse::<u64>().ok()?,
+            ("UNTIL", value) => until = Some(parse_date("", value)?),
+            ("COUNT", value) => count = Some(value.parse::<u64>().ok()?),
+            ("WKST", value) => week_start = value,
+            ("BYDAY", value) => {
+                for day in value.split(',') {
+                    let day = RRULE_DAYS.iter().position(|other| *other == day)?;
+                    week_days.push(day as u32);
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    let (recurrence, step) = match (freq, interval, week_days.is_empty()) {
+        ("DAILY", 1, true) => (Recurrence::Daily, 1),
+        // DAILY;BYDAY=MO,TU,WE,TH,FR — то же, что еженедельно по будням
+        ("DAILY", 1, false) | ("WEEKLY", 1, _) => (Recurrence::Weekly, 7),
+        // Через неделю считаем от понедельника недели DTSTART
+        ("WEEKLY", 2, _) if week_start == "MO" => (Recurrence::BiWeekly, 14),
+        _ => return None,
+    };
+
+    if week_days.is_empty() {
+        week_days.push(first.weekday().num_days_from_monday());
+    }
+    week_days.sort();
+    week_days.dedup();
+
+    let monday = first - Days::new(first.weekday().num_days_from_monday() as u64);
+    let mut series: Vec<Series> = week_days
+        .iter()
+        .map(|day| {
+            let date = monday + Days::new(*day as u64);
+            Series {
+                first: if date < first {
+                    date + Days::new(step)
+                } else {
+                    date
+                },
+                recurrence,
+                until,
+            }
+        })
+        .collect();
+
+    // COUNT считает повторения всех серий вместе, начиная с DTSTART
+    if let Some(count) = count {
+        let mut date = first;
+        let mut left = count;
+        let last = loop {
+            let occurs = series.iter().any(|series| {
+                date >= series.first
+                    && ((date - series.first).num_days() as u64).is_multiple_of(step)
+            });
+            if occurs {
+                left = left.saturating_sub(1);
+                if left == 0 {
+                    break date;
+                }
+            }
+            date = date.succ_opt()?;
+        };
+
+        for series in &mut series {
+            series.until = Some(series.until.map_or(last, |until| until.min(last)));
+        }
+    }
+
+    Some(series)
+}
+
+type Lines = [(String, String, String)];
+
+fn property<'a>(lines: &'a Lines, name: &str) -> Option<&'a (String, String, String)> {
+    lines.iter().find(|(key, _, _)| key == name)
+}
+
+fn parse_vevent(
+    lines: &Lines,
+    mut exceptions: Vec<NaiveDate>,
+    skipped: &mut Vec<String>,
+) -> Vec<PersonalEvent> {
+    let title = property(lines, "SUMMARY")
+        .map(|(_, _, value)| unescape_text(value))
+        .unwrap_or_default();
+    if property(lines, "STATUS").is_some_and(|(_, _, value)| value.trim() == "CANCELLED") {
+        return vec![];
+    }
+
+    let Some((_, params, value)) = property(lines, "DTSTART") else {
+        return vec![];
+    };
+    let Some(start) = parse_date_time(params, value) else {
+        return vec![];
+    };
+    let end = match property(lines, "DTEND") {
+        Some((_, params, value)) => match parse_date_time(params, value) {
+            Some(end) => end,
+            None => return vec![],
+        },
+        None => start,
+    };
+    // Событие через полночь обрезаем концом дня
+    let time_end = if end.date() != start.date() {
+        NaiveTime::from_hms_opt(23, 59, 0).unwrap()
+    } else {
+        end.time()
+    };
+
+    let series = match property(lines, "RRULE") {
+        Some((_, _, rule)) => match parse_rrule(rule, start.date()) {
+            Some(series) => series,
+            None => {
+                skipped.push(title);
+                return vec![];
+            }
+        },
+        None => vec![Series {
+            first: start.date(),
+            recurrence: Recurrence::Once,
+            until: None,
+        }],
+    };
+
+    // EXDATE может повторяться и содержать несколько дат через запятую
+    for (key, params, value) in lines {
+        if key == "EXDATE" {
+            exceptions.extend(value.split(',').filter_map(|date| parse_date(params, date)));
+        }
+    }
+
+    series
+        .into_iter()
+        .map(|series| PersonalEvent {
+            title: title.clone(),
+            date: series.first.format("%d.%m.%Y").to_string(),
+            time_start: start.format("%H:%M").to_string(),
+            time_end: time_end.format("%H:%M").to_string(),
+            place: property(lines, "LOCATION").map(|(_, _, value)| unescape_text(value)),
+            recurrence: series.recurrence,
+            until: series
+                .until
+                .map(|until| until.format("%d.%m.%Y").to_string()),
+            external: true,
+            exceptions: exceptions.clone(),
+        })
+        .collect()
+}
+
+pub struct IcalEvents {
+    pub events: Vec<PersonalEvent>,
+    /// Названия событий с правилами повторения, которые мы не умеем показывать
+    pub skipped: Vec<String>,
+}
+
+/// Разбирает VEVENT'ы из .ics (например, рабочего или спортивного календаря)
+pub fn parse_ical_events(text: &str) -> IcalEvents {
+    // RFC 5545: строка, начинающаяся с пробела или таба, продолжает предыдущую
+    let mut unfolded: Vec<String> = vec![];
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), unfolded.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => unfolded.push(line.to_string()),
+        }
+    }
+
+    let mut components: Vec<Vec<(String, String, String)>> = vec![];
+    let mut current: Option<Vec<(String, String, String)>> = None;
+
+    for line in unfolded {
+        let (name, value) = match line.split_once(':') {
+            Some(pair) => pair,
+            None => continue,
+        };
+        let (key, params) = name.split_once(';').unwrap_or((name, ""));
+        let key = key.to_uppercase();
+
+        match (key.as_str(), value.trim()) {
+            ("BEGIN", "VEVENT") => current = Some(vec![]),
+            ("END", "VEVENT") => components.extend(current.take()),
+            // VALARM и прочие вложенные компоненты нам не нужны
+            ("BEGIN", _) | ("END", _) => (),
+            _ => {
+                if let Some(lines) = current.as_mut() {
+                    lines.push((key, params.to_string(), value.to_string()));
+                }
+            }
+        }
+    }
+
+    // RECURRENCE-ID: перенесённое или отменённое повторение серии с тем же UID.
+    // Само оно — отдельное разовое событие, а в серии эта дата пропускается
+    let uid = |lines: &Lines| property(lines, "UID").map(|(_, _, value)| value.trim().to_string());
+    let overrides: Vec<(String, NaiveDate)> = components
+        .iter()
+        .filter_map(|lines| {
+            let (_, params, value) = property(lines, "RECURRENCE-ID")?;
+            Some((uid(lines)?, parse_date(params, value)?))
+        })
+        .collect();
+
+    let mut result = IcalEvents {
+        events: vec![],
+        skipped: vec![],
+    };
+    for lines in &components {
+        let exceptions = match (property(lines, "RECURRENCE-ID"), uid(lines)) {
+            (None, Some(uid)) => overrides
+                .iter()
+                .filter(|(other, _)| *other == uid)
+                .map(|(_, date)| *date)
+                .collect(),
+            _ => vec![],
+        };
+        result
+            .events
+            .extend(parse_vevent(lines, exceptions, &mut result.skipped));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%d.%m.%Y").unwrap()
+    }
+
+    fn calendar(events: &[&str]) -> String {
+        let mut text = String::from("BEGIN:VCALENDAR\r\n");
+        for event in events {
+            text.push_str(&format!(
+                "BEGIN:VEVENT\r\n{}\r\nEND:VEVENT\r\n",
+                event.trim()
+            ));
+        }
+        text.push_str("END:VCALENDAR\r\n");
+        text
+    }
+
+    fn occurs(events: &[PersonalEvent], day: &str) -> bool {
+        events.iter().any(|event| event.occurs_on(date(day)))
+    }
+
+    fn lesson(groups: &str, teacher: &str, classroom: &str) -> LessonItem {
+        LessonItem {
+            time_start: "09:30".to_string(),
+            time_end: "11:00".to_string(),
+            text: "Английский язык".to_string(),
+            additional: crate::schedules::additional::AdditionalLessonInfo {
+                teacher_name: Some(teacher.to_string()),
+                classroom: Some(classroom.to_string()),
+                online: false,
+                groups: Some(groups.to_string()),
+                r#type: crate::schedules::additional::LessonType::Practice,
+            },
+            urls: vec![],
+            slot: 0,
+        }
+    }
+
+    fn uid(lesson: &LessonItem) -> String {
+        let mut out = String::new();
+        push_event(&mut out, date("03.11.2025"), lesson, &Utc::now());
+        out.lines()
+            .find_map(|line| line.strip_prefix("UID:"))
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn subgroups_get_own_uids() {
+        let first = lesson("1 подгруппа", "Иванова А.Б.", "А-101");
+        let second = lesson("2 подгруппа", "Петров В.Г.", "А-102");
+        assert_ne!(uid(&first), uid(&second));
+
+        // перенос в другую аудиторию обновляет то же событие
+        let moved = lesson("1 подгруппа", "Иванова А.Б.", "Б-305");
+        assert_eq!(uid(&first), uid(&moved));
+    }
+
+    #[test]
+    fn weekly_by_day() {
+        // 03.11.2025 — понедельник
+        let parsed = parse_ical_events(&calendar(&["UID:a
+SUMMARY:Работа
+DTSTART:20251103T180000
+DTEND:20251103T200000
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE"]));
+
+        assert!(parsed.skipped.is_empty());
+        assert!(occurs(&parsed.events, "03.11.2025"));
+        assert!(occurs(&parsed.events, "05.11.2025"));
+        assert!(occurs(&parsed.events, "10.11.2025"));
+        assert!(occurs(&parsed.events, "12.11.2025"));
+        assert!(!occurs(&parsed.events, "04.11.2025"));
+    }
+
+    #[test]
+    fn by_day_before_start_and_count() {
+        // DTSTART в среду, понедельник той же недели уже прошёл; всего 3 повторения
+        let parsed = parse_ical_events(&calendar(&["UID:a
+DTSTART:20251105T180000
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=3"]));
+
+        assert!(!occurs(&parsed.events, "03.11.2025"));
+        assert!(occurs(&parsed.events, "05.11.2025"));
+        assert!(occurs(&parsed.events, "10.11.2025"));
+        assert!(occurs(&parsed.events, "12.11.2025"));
+        assert!(!occurs(&parsed.events, "17.11.2025"));
+    }
+
+    #[test]
+    fn biweekly_by_day() {
+        let parsed = parse_ical_events(&calendar(&["UID:a
+DTSTART:20251103T180000
+RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH"]));
+
+        assert!(occurs(&parsed.events, "03.11.2025"));
+        assert!(occurs(&parsed.events, "06.11.2025"));
+        assert!(!occurs(&parsed.events, "10.11.2025"));
+        assert!(!occurs(&parsed.events, "13.11.2025"));
+        assert!(occurs(&parsed.events, "17.11.2025"));
+        assert!(occurs(&parsed.events, "20.11.2025"));
+    }
+
+    #[test]
+    fn exdate() {
+        let parsed = parse_ical_events(&calendar(&["UID:a
+DTSTART;TZID=Europe/Moscow:20251103T180000
+RRULE:FREQ=WEEKLY
+EXDATE;TZID=Europe/Moscow:20251110T180000,20251117T180000
+EXDATE;VALUE=DATE:20251201"]));
+
+        assert!(occurs(&parsed.events, "03.11.2025"));
+        assert!(!occurs(&parsed.events, "10.11.2025"));
+        assert!(!occurs(&parsed.events, "17.11.2025"));
+        assert!(occurs(&parsed.events, "24.11.2025"));
+        assert!(!occurs(&parsed.events, "01.12.2025"));
+    }
+
+    #[test]
+    fn recurrence_id_override() {
+        let parsed = parse_ical_events(&calendar(&[
+            "UID:a
+SUMMARY:Тренировка
+DTSTART:20251103T180000
+DTEND:20251103T190000
+RRULE:FREQ=WEEKLY",
+            "UID:a
+SUMMARY:Тренировка
+RECURRENCE-ID:20251110T180000
+DTSTART:20251111T190000
+DTEND:20251111T200000",
+            "UID:a
+RECURRENCE-ID:20251117T180000
+DTSTART:20251117T180000
+STATUS:CANCELLED",
+        ]));
+
+        let on = |day: &str| -> Vec<&PersonalEvent> {
+            parsed
+                .events
+                .iter()
+                .filter(|event| event.occurs_on(date(day)))
+                .collect()
+        };
+        assert!(on("10.11.2025").is_empty());
+        assert_eq!(on("11.11.2025").len(), 1);
+        assert_eq!(on("11.11.2025")[0].time_start, "19:00");
+        assert!(on("17.11.2025").is_empty());
+        assert_eq!(on("24.11.2025").len(), 1);
+    }
+
+    #[test]
+    fn unsupported_rules_are_skipped() {
+        let parsed = parse_ical_events(&calendar(&[
+            "UID:a
+SUMMARY:Monthly
+DTSTART:20251103T180000
+RRULE:FREQ=MONTHLY",
+            "UID:b
+SUMMARY:Every third week
+DTSTART:20251103T180000
+RRULE:FREQ=WEEKLY;INTERVAL=3",
+            "UID:c
+SUMMARY:First Monday
+DTSTART:20251103T180000
+RRULE:FREQ=WEEKLY;BYDAY=1MO",
+        ]));
+
+        assert!(parsed.events.is_empty());
+        assert_eq!(
+            parsed.skipped,
+            ["Monthly", "Every third week", "First Monday"]
+        );
+    }
+}
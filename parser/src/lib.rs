@@ -1,7 +1,19 @@
+use chrono::NaiveDate;
+
+pub mod diploma;
 pub mod errors;
+pub mod events;
+pub mod filters;
+pub mod gaps;
 pub mod grades;
+pub mod ical;
 pub mod schedules;
+pub mod scholarship;
 pub mod lists;
+pub mod matching;
+pub mod search;
+pub mod stats;
+pub mod timezone;
 
 pub async fn get_schedules(
     date_from: &str,
@@ -19,10 +31,53 @@ pub async fn get_schedules(
         ("group", &group_id),
         ("tuttabl", "0"),
     ];
+
+    fetch_schedules(&params).await
+}
+
+pub async fn get_teacher_schedules(
+    date_from: &str,
+    date_to: &str,
+    teacher_id: &str,
+) -> Result<Vec<schedules::DayItem>, String> {
+    let params = [
+        ("exam", "0"),
+        ("formo", "0"),
+        ("allp", "0"),
+        ("hour", "0"),
+        ("datafrom", date_from),
+        ("dataend", date_to),
+        ("rtype", "2"),
+        ("teacher", teacher_id),
+        ("tuttabl", "0"),
+    ];
+
+    fetch_schedules(&params).await
+}
+
+pub async fn search_schedules(
+    date_from: NaiveDate,
+    date_to: NaiveDate,
+    group_id: &str,
+    query: &str,
+    filters: &filters::LessonFilters,
+) -> Result<Vec<search::SearchMatch>, String> {
+    let mut days = get_schedules(
+        &date_from.format("%d.%m.%Y").to_string(),
+        &date_to.format("%d.%m.%Y").to_string(),
+        group_id,
+    )
+    .await?;
+    filters.apply(&mut days);
+
+    Ok(search::search_days(days, date_from, query))
+}
+
+async fn fetch_schedules(params: &[(&str, &str)]) -> Result<Vec<schedules::DayItem>, String> {
     let client = reqwest::Client::new();
     let request = client
         .post("http://inet.ibi.spb.ru/raspisan/rasp.php")
-        .form(&params)
+        .form(params)
         .send()
         .await;
 
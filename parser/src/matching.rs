@@ -0,0 +1,129 @@
+use chrono::NaiveDate;
+
+use crate::{
+    grades::{GradeItem, GradeType, group_attempts},
+    schedules::{DayItem, LessonItem, additional::LessonType},
+    scholarship::latest_semester,
+};
+
+/// Служебные слова и остатки пометок о подгруппах, на сравнение не влияют
+const NOISE_WORDS: [&str; 12] = [
+    "и",
+    "в",
+    "на",
+    "по",
+    "с",
+    "для",
+    "п",
+    "гр",
+    "подгр",
+    "подгруппа",
+    "я",
+    "web",
+];
+
+/// Слова названия: без регистра, пунктуации, «ё» и номеров
+fn subject_words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .replace('ё', "е")
+        .split(|char: char| !char.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .filter(|word| !word.chars().all(|char| char.is_ascii_digit()))
+        .filter(|word| !NOISE_WORDS.contains(word))
+        .map(|word| word.to_string())
+        .collect()
+}
+
+/// «мат» и «математический» — одно слово, если одно начинается с другого
+fn words_match(a: &str, b: &str) -> bool {
+    let (short, long) = if a.chars().count() <= b.chars().count() {
+        (a, b)
+    } else {
+        (b, a)
+    };
+
+    short.chars().count() >= 2 && long.starts_with(short)
+}
+
+/// «БЖД» против «Безопасность жизнедеятельности»
+fn is_acronym(acronym: &[String], words: &[String]) -> bool {
+    if acronym.len() != 1 || words.len() < 2 {
+        return false;
+    }
+
+    let initials: String = words
+        .iter()
+        .filter_map(|word| word.chars().next())
+        .collect();
+    acronym[0].chars().count() >= 2 && acronym[0] == initials
+}
+
+/// Названия на странице оценок и в расписании пишутся по-разному:
+/// сокращения, точки, пометки о подгруппах
+pub fn subjects_match(a: &str, b: &str) -> bool {
+    let (a, b) = (subject_words(a), subject_words(b));
+
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+
+    if a.len() == b.len() && a.iter().zip(&b).all(|(a, b)| words_match(a, b)) {
+        return true;
+    }
+
+    is_acronym(&a, &b) || is_acronym(&b, &a)
+}
+
+/// Какое занятие в расписании соответствует форме контроля в ведомости
+pub fn types_match(grade: &GradeType, lesson: LessonType) -> bool {
+    match grade {
+        GradeType::Exam | GradeType::GovExam => matches!(lesson, LessonType::Exam),
+        GradeType::SubjectReport => matches!(lesson, LessonType::SubjectReport),
+        GradeType::SubjectReportWithGrade => matches!(
+            lesson,
+            LessonType::SubjectReportWithGrade | LessonType::SubjectReport
+        ),
+        GradeType::OfflineCourseWork | GradeType::OnlineCourseWork => {
+            matches!(lesson, LessonType::CourseWorkDefend)
+        }
+        GradeType::Unknown => lesson.is_assessment(),
+    }
+}
+
+/// Итоговая запись текущего семестра по занятию-аттестации. Прошлые семестры не смотрим:
+/// зачёт по дисциплине, которая идёт каждый семестр, там уже стоит.
+/// Возвращает номер семестра (с 1) и последнюю попытку
+pub fn find_grade<'a>(
+    semesters: &'a [Vec<GradeItem>; 8],
+    lesson: &LessonItem,
+) -> Option<(usize, &'a GradeItem)> {
+    if !lesson.additional.r#type.is_assessment() {
+        return None;
+    }
+    let index = latest_semester(semesters)?;
+
+    group_attempts(index + 1, &semesters[index])
+        .into_iter()
+        .find(|discipline| {
+            types_match(discipline.r#type(), lesson.additional.r#type)
+                && subjects_match(discipline.name(), &lesson.text)
+        })
+        .map(|discipline| (index + 1, discipline.final_item()))
+}
+
+/// Все занятия-аттестации в расписании по записи ведомости, по порядку
+pub fn find_assessments<'a>(
+    days: &'a [DayItem],
+    date_from: NaiveDate,
+    item: &GradeItem,
+) -> Vec<(NaiveDate, &'a LessonItem)> {
+    days.iter()
+        .filter_map(|day| Some((day.date(date_from)?, day)))
+        .flat_map(|(date, day)| day.lessons.iter().map(move |lesson| (date, lesson)))
+        .filter(|(_, lesson)| {
+            lesson.additional.r#type.is_assessment()
+                && types_match(&item.r#type, lesson.additional.r#type)
+                && subjects_match(&item.name, &lesson.text)
+        })
+        .collect()
+}
@@ -1,5 +1,6 @@
 use regex::Regex;
 
+#[derive(Clone)]
 pub struct AdditionalLessonInfo {
     pub teacher_name: Option<String>,
     pub classroom: Option<String>,
@@ -19,6 +20,8 @@ pub enum LessonType {
     SubjectReportWithGrade,
     CourseWorkDefend,
     Meeting,
+    Personal,
+    External,
 }
 
 fn parse_classroom(text: String) -> (Option<String>, String) {
@@ -41,6 +44,18 @@ fn parse_classroom(text: String) -> (Option<String>, String) {
     }
 }
 
+// "1 п/гр", "2-я подгруппа", "подгр. 1"
+fn parse_subgroup(text: &str) -> Option<String> {
+    let subgroup_regex = Regex::new(
+        r"(?i)([0-9])\s*(-?я)?\s*(п/гр?\.?|подгр\.?|подгруппа)|(п/гр?\.?|подгр\.?|подгруппа)\s*№?\s*([0-9])",
+    )
+    .unwrap();
+
+    subgroup_regex
+        .find(text)
+        .map(|subgroup| subgroup.as_str().trim().to_string())
+}
+
 fn parse_teacher(text: String) -> (String, String) {
     let teacher_regex = Regex::new(r", .* .\..\.").unwrap();
     let teacher = teacher_regex.find(&text);
@@ -108,6 +123,23 @@ impl LessonType {
         (Self::Unknown, text.to_string())
     }
 
+    /// Русское название, как его пишут в расписании
+    pub fn to_label(self) -> &'static str {
+        match self {
+            Self::Lecture => "Лекция",
+            Self::Practice => "Практика",
+            Self::Exam => "Экзамен",
+            Self::SubjectReport => "Зачёт",
+            Self::SubjectReportWithGrade => "Дифф. зачёт",
+            Self::Consultation => "Консультация",
+            Self::CourseWorkDefend => "Защита КР",
+            Self::Meeting => "Собрание",
+            Self::Personal => "Личное",
+            Self::External => "Календарь",
+            Self::Unknown => "",
+        }
+    }
+
     pub fn to_text(self) -> String {
         match self {
             Self::Consultation => "consultation".to_string(),
@@ -119,11 +151,39 @@ impl LessonType {
             Self::CourseWorkDefend => "course_work_defend".to_string(),
             Self::Unknown => "unknown".to_string(),
             Self::Meeting => "meeting".to_string(),
+            Self::Personal => "personal".to_string(),
+            Self::External => "external".to_string(),
         }
     }
+
+    /// Занятие из расписания ИБИ, а не добавленное пользователем
+    pub fn is_official(self) -> bool {
+        !matches!(self, Self::Personal | Self::External)
+    }
+
+    /// Экзамен, зачёт или защита курсовой — то, за что потом ставят оценку
+    pub fn is_assessment(self) -> bool {
+        matches!(
+            self,
+            Self::Exam
+                | Self::SubjectReport
+                | Self::SubjectReportWithGrade
+                | Self::CourseWorkDefend
+        )
+    }
 }
 
 impl AdditionalLessonInfo {
+    /// Номер подгруппы, если занятие не для всей группы
+    pub fn subgroup(&self) -> Option<u8> {
+        self.groups
+            .as_ref()?
+            .chars()
+            .find(|c| c.is_ascii_digit())?
+            .to_digit(10)
+            .map(|digit| digit as u8)
+    }
+
     pub fn new(text: String) -> (Self, String) {
         let mut result = Self {
             teacher_name: None,
@@ -150,6 +210,8 @@ impl AdditionalLessonInfo {
             result.online = true;
         }
 
+        result.groups = parse_subgroup(&text);
+
         let (r#type, text) = LessonType::parse_from_text(&text);
         result.r#type = r#type;
 
@@ -1,30 +1,86 @@
-use chrono::{Duration, NaiveTime};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime};
 use regex::Regex;
 use scraper::{Html, Selector};
+use serde_derive::{Deserialize, Serialize};
 use std::vec;
 
 use crate::errors::ErrorCode;
 pub mod additional;
 
+#[derive(Clone)]
 pub struct LessonItem {
     pub time_start: String,
     pub time_end: String,
     pub text: String,
     pub additional: additional::AdditionalLessonInfo,
     pub urls: Vec<LessonUrl>,
+    /// Номер пары (индекс в `DayItem::slots`)
+    pub slot: usize,
 }
 
+/// Идентификатор занятия: дата + номер пары + предмет
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct LessonKey {
+    /// %Y-%m-%d
+    pub date: String,
+    pub slot: usize,
+    pub subject: String,
+}
+
+impl LessonItem {
+    pub fn key(&self, date: NaiveDate) -> LessonKey {
+        LessonKey {
+            date: date.format("%Y-%m-%d").to_string(),
+            slot: self.slot,
+            subject: self.text.trim().to_string(),
+        }
+    }
+}
+
+impl LessonKey {
+    pub fn date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.date, "%Y-%m-%d").ok()
+    }
+}
+
+/// Время пары по звонкам, из шапки таблицы
+#[derive(Clone)]
+pub struct BellSlot {
+    pub time_start: String,
+    pub time_end: String,
+}
+
+#[derive(Clone)]
 pub struct DayItem {
     pub day: String,
     pub month: String,
     pub week_day: String,
     pub lessons: Vec<LessonItem>,
+    pub slots: Vec<BellSlot>,
+}
+
+impl DayItem {
+    /// Таблица содержит только день и месяц, год восстанавливаем по началу периода
+    pub fn date(&self, date_from: NaiveDate) -> Option<NaiveDate> {
+        let (day, month) = (
+            self.day.trim().parse::<u32>().ok()?,
+            self.month.trim().parse::<u32>().ok()?,
+        );
+        let date = NaiveDate::from_ymd_opt(date_from.year(), month, day)?;
+
+        if date < date_from {
+            return NaiveDate::from_ymd_opt(date_from.year() + 1, month, day);
+        }
+
+        Some(date)
+    }
 }
 
 fn remove_nbsp(string: &str) -> String {
     string.replace("&nbsp;", "")
 }
 
+#[derive(Clone)]
 pub struct LessonUrl {
     pub text: String,
     pub url: String,
@@ -119,6 +175,17 @@ pub fn parse_schedules_table(html: String) -> Result<Vec<DayItem>, String> {
     let lesson_count = first_row.child_elements().count() - 1;
     let mut days: Vec<DayItem> = vec![];
 
+    let slots: Vec<BellSlot> = times
+        .map(|time_el| {
+            let time_el_text = time_el.text().collect::<Vec<_>>().join(" ");
+            let mut start_end = time_el_text.split("-");
+            BellSlot {
+                time_start: remove_nbsp(start_end.next().unwrap_or("")).trim().to_string(),
+                time_end: remove_nbsp(start_end.next().unwrap_or("")).trim().to_string(),
+            }
+        })
+        .collect();
+
     for rowcol in 2..rows_count {
         let day_month_el = rows.clone().nth(rowcol).unwrap().child_elements().nth(0);
         match day_month_el {
@@ -141,24 +208,21 @@ pub fn parse_schedules_table(html: String) -> Result<Vec<DayItem>, String> {
                         month: month.to_string(),
                         week_day: week_day.to_string(),
                         lessons: vec![],
+                        slots: slots.clone(),
                     },
                 );
 
                 for col in 0..=lesson_count {
                     let mut cols = rows.clone().nth(rowcol).unwrap().child_elements();
-                    let text_el = cols.nth(col + 1);
-                    let time_el = times.clone().nth(col);
-
-                    let time_el_text = time_el.unwrap().text().collect::<Vec<_>>().join(" ");
-                    let mut start_end = time_el_text.split("-");
-                    let (start, end) = (
-                        remove_nbsp(start_end.nth(0).unwrap()),
-                        remove_nbsp(start_end.next().unwrap()),
-                    );
+                    // строка времени короче строки с парами — такую пару не к чему привязать
+                    let (Some(text_el), Some(slot)) = (cols.nth(col + 1), slots.get(col)) else {
+                        continue;
+                    };
+                    let (start, end) = (&slot.time_start, &slot.time_end);
 
-                    let text_el_text = text_el.unwrap().text().collect::<Vec<_>>().join(" ");
+                    let text_el_text = text_el.text().collect::<Vec<_>>().join(" ");
                     if text_el_text.trim() != "" {
-                        let (urls, text) = parse_urls(text_el.unwrap().inner_html());
+                        let (urls, text) = parse_urls(text_el.inner_html());
                         let (additional, text) = additional::AdditionalLessonInfo::new(text);
 
                         match detect_custom_time(&text) {
@@ -169,15 +233,17 @@ pub fn parse_schedules_table(html: String) -> Result<Vec<DayItem>, String> {
                                     text: text.replace(", ", ""),
                                     additional,
                                     urls,
+                                    slot: col,
                                 });
                             }
                             None => {
                                 days[rowcol - 2].lessons.push(LessonItem {
-                                    time_start: start.trim().to_string(),
-                                    time_end: end.trim().to_string(),
+                                    time_start: start.to_string(),
+                                    time_end: end.to_string(),
                                     text: text.replace(", ", ""),
                                     additional,
                                     urls,
+                                    slot: col,
                                 });
                             }
                         }
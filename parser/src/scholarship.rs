@@ -0,0 +1,140 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::grades::{GradeItem, GradeResult, GradeType, academic_debts};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScholarshipRule {
+    /// Минимальная оценка за экзамены и дифзачёты: 4 — без троек, 5 — только отлично
+    pub min_grade: u8,
+    /// Не должно быть академических задолженностей ни за один семестр
+    #[serde(default)]
+    pub no_debts: bool,
+    /// Все зачёты семестра сданы
+    #[serde(default)]
+    pub all_credits_passed: bool,
+}
+
+/// Правила хранятся в отдельном файле, чтобы их можно было поправить под своё положение о стипендии
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScholarshipRules {
+    pub standard: ScholarshipRule,
+    pub increased: ScholarshipRule,
+}
+
+impl Default for ScholarshipRules {
+    fn default() -> Self {
+        Self {
+            standard: ScholarshipRule {
+                min_grade: 4,
+                no_debts: true,
+                all_credits_passed: true,
+            },
+            increased: ScholarshipRule {
+                min_grade: 5,
+                no_debts: true,
+                all_credits_passed: true,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BlockReason {
+    LowGrade,
+    Debt,
+    CreditNotPassed,
+    /// Оценка ещё не выставлена
+    Pending,
+}
+
+impl BlockReason {
+    pub fn to_text(self) -> &'static str {
+        match self {
+            Self::LowGrade => "low_grade",
+            Self::Debt => "debt",
+            Self::CreditNotPassed => "credit_not_passed",
+            Self::Pending => "pending",
+        }
+    }
+}
+
+pub struct Blocker<'a> {
+    /// Номер семестра, с 1
+    pub semester: usize,
+    pub item: &'a GradeItem,
+    pub reason: BlockReason,
+}
+
+pub struct Verdict<'a> {
+    /// Номер семестра, по которому считали, с 1
+    pub semester: Option<usize>,
+    pub blockers: Vec<Blocker<'a>>,
+}
+
+impl Verdict<'_> {
+    pub fn eligible(&self) -> bool {
+        self.semester.is_some() && self.blockers.is_empty()
+    }
+}
+
+/// Последний семестр, в котором есть записи
+pub fn latest_semester(semesters: &[Vec<GradeItem>; 8]) -> Option<usize> {
+    semesters.iter().rposition(|semester| !semester.is_empty())
+}
+
+pub fn check_scholarship<'a>(
+    semesters: &'a [Vec<GradeItem>; 8],
+    rule: &ScholarshipRule,
+) -> Verdict<'a> {
+    let index = match latest_semester(semesters) {
+        Some(index) => index,
+        None => {
+            return Verdict {
+                semester: None,
+                blockers: vec![],
+            };
+        }
+    };
+    let mut blockers: Vec<Blocker> = vec![];
+
+    for item in &semesters[index] {
+        let reason = match (&item.r#type, &item.grade) {
+            (_, GradeResult::Unknown) => Some(BlockReason::Pending),
+            (GradeType::SubjectReport, GradeResult::Passed) => None,
+            (GradeType::SubjectReport, _) if rule.all_credits_passed => {
+                Some(BlockReason::CreditNotPassed)
+            }
+            (_, grade) if grade.is_failing() && rule.no_debts => Some(BlockReason::Debt),
+            (_, grade) => match grade.value() {
+                Some(value) if value < rule.min_grade => Some(BlockReason::LowGrade),
+                _ => None,
+            },
+        };
+
+        if let Some(reason) = reason {
+            blockers.push(Blocker {
+                semester: index + 1,
+                item,
+                reason,
+            });
+        }
+    }
+
+    // Хвосты за прошлые семестры тоже лишают стипендии
+    if rule.no_debts {
+        for debt in academic_debts(semesters) {
+            if debt.semester != index + 1 {
+                blockers.push(Blocker {
+                    semester: debt.semester,
+                    item: debt.item,
+                    reason: BlockReason::Debt,
+                });
+            }
+        }
+    }
+
+    Verdict {
+        semester: Some(index + 1),
+        blockers,
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::NaiveDate;
+
+use crate::schedules::{DayItem, LessonItem};
+
+pub struct SearchMatch {
+    pub date: NaiveDate,
+    pub week_day: String,
+    pub lesson: LessonItem,
+}
+
+fn normalize(text: &str) -> String {
+    text.to_lowercase().replace('ё', "е")
+}
+
+/// Короче совпадения по типу и «онлайн» не ищем: «a» или «e» нашлись бы почти везде
+const MIN_KEYWORD_LEN: usize = 3;
+
+// "лекции", "практ", "practice" -> совпадение с началом слова, достаточно первых четырёх букв
+fn keyword_matches(keywords: &str, term: &str) -> bool {
+    if term.chars().count() < MIN_KEYWORD_LEN {
+        return false;
+    }
+
+    let stem: String = term.chars().take(4).collect();
+    keywords
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| !word.is_empty() && word.starts_with(&stem))
+}
+
+fn type_matches(lesson: &LessonItem, term: &str) -> bool {
+    let r#type = lesson.additional.r#type;
+    let label = normalize(r#type.to_label());
+
+    if label.is_empty() {
+        return false;
+    }
+
+    keyword_matches(&format!("{} {}", label, r#type.to_text()), term)
+}
+
+/// Каждое слово запроса должно встретиться в названии, преподавателе, аудитории или типе занятия
+pub fn lesson_matches(lesson: &LessonItem, query: &str) -> bool {
+    let fields = [
+        normalize(&lesson.text),
+        normalize(&lesson.additional.teacher_name.clone().unwrap_or_default()),
+        normalize(&lesson.additional.classroom.clone().unwrap_or_default()),
+    ];
+    let query = normalize(query);
+    let mut terms = query.split_whitespace().peekable();
+
+    if terms.peek().is_none() {
+        return false;
+    }
+
+    terms.all(|term| {
+        fields.iter().any(|field| field.contains(term))
+            || type_matches(lesson, term)
+            || (lesson.additional.online && keyword_matches("онлайн online", term))
+    })
+}
+
+pub fn search_days(days: Vec<DayItem>, date_from: NaiveDate, query: &str) -> Vec<SearchMatch> {
+    let mut result: Vec<SearchMatch> = vec![];
+
+    for day in days {
+        let date = match day.date(date_from) {
+            Some(date) => date,
+            None => continue,
+        };
+
+        for lesson in day.lessons {
+            if lesson_matches(&lesson, query) {
+                result.push(SearchMatch {
+                    date,
+                    week_day: day.week_day.clone(),
+                    lesson,
+                });
+            }
+        }
+    }
+
+    result
+}
@@ -0,0 +1,165 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::schedules::{DayItem, additional::LessonType};
+
+pub struct TypeStats {
+    pub r#type: LessonType,
+    pub total: usize,
+    pub past: usize,
+    pub minutes: i64,
+}
+
+pub struct SubjectStats {
+    pub subject: String,
+    pub teachers: Vec<String>,
+    pub types: Vec<TypeStats>,
+}
+
+impl SubjectStats {
+    pub fn total(&self) -> usize {
+        self.types.iter().map(|item| item.total).sum()
+    }
+
+    pub fn past(&self) -> usize {
+        self.types.iter().map(|item| item.past).sum()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.total() - self.past()
+    }
+
+    pub fn minutes(&self) -> i64 {
+        self.types.iter().map(|item| item.minutes).sum()
+    }
+}
+
+/// Осенний семестр с сессией: сентябрь — январь, весенний: февраль — июль.
+/// В августе показываем наступающий осенний.
+pub fn semester_bounds(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let year = date.year();
+
+    match date.month() {
+        1 => (
+            NaiveDate::from_ymd_opt(year - 1, 9, 1).unwrap(),
+            NaiveDate::from_ymd_opt(year, 1, 31).unwrap(),
+        ),
+        2..=7 => (
+            NaiveDate::from_ymd_opt(year, 2, 1).unwrap(),
+            NaiveDate::from_ymd_opt(year, 7, 31).unwrap(),
+        ),
+        _ => (
+            NaiveDate::from_ymd_opt(year, 9, 1).unwrap(),
+            NaiveDate::from_ymd_opt(year + 1, 1, 31).unwrap(),
+        ),
+    }
+}
+
+/// Нагрузка по предметам и типам занятий; личные и внешние события не учитываются
+pub fn workload_stats(
+    days: &[DayItem],
+    date_from: NaiveDate,
+    now: NaiveDateTime,
+) -> Vec<SubjectStats> {
+    let mut result: Vec<SubjectStats> = vec![];
+
+    for day in days {
+        let date = match day.date(date_from) {
+            Some(date) => date,
+            None => continue,
+        };
+
+        for lesson in &day.lessons {
+            if !lesson.additional.r#type.is_official() {
+                continue;
+            }
+
+            let (start, end) = match (
+                NaiveTime::parse_from_str(lesson.time_start.trim(), "%H:%M"),
+                NaiveTime::parse_from_str(lesson.time_end.trim(), "%H:%M"),
+            ) {
+                (Ok(start), Ok(end)) => (start, end),
+                _ => continue,
+            };
+
+            let subject = lesson.text.trim().to_string();
+            let index = match result.iter().position(|item| item.subject == subject) {
+                Some(index) => index,
+                None => {
+                    result.push(SubjectStats {
+                        subject,
+                        teachers: vec![],
+                        types: vec![],
+                    });
+                    result.len() - 1
+                }
+            };
+            let stats = &mut result[index];
+
+            if let Some(teacher) = &lesson.additional.teacher_name
+                && !teacher.is_empty()
+                && !stats.teachers.contains(teacher)
+            {
+                stats.teachers.push(teacher.clone());
+            }
+
+            let r#type = lesson.additional.r#type;
+            let type_index = match stats
+                .types
+                .iter()
+                .position(|item| item.r#type.to_text() == r#type.to_text())
+            {
+                Some(index) => index,
+                None => {
+                    stats.types.push(TypeStats {
+                        r#type,
+                        total: 0,
+                        past: 0,
+                        minutes: 0,
+                    });
+                    stats.types.len() - 1
+                }
+            };
+            let type_stats = &mut stats.types[type_index];
+
+            type_stats.total += 1;
+            type_stats.minutes += (end - start).num_minutes();
+            if date.and_time(end) <= now {
+                type_stats.past += 1;
+            }
+        }
+    }
+
+    result.sort_by(|a, b| a.subject.cmp(&b.subject));
+    result
+}
+
+pub(crate) fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Одна строка на пару «предмет + тип занятия»
+pub fn workload_to_csv(stats: &[SubjectStats]) -> String {
+    let mut out = String::from("Предмет,Тип,Всего,Прошло,Осталось,Часов,Преподаватели\n");
+
+    for subject in stats {
+        for item in &subject.types {
+            let label = item.r#type.to_label();
+            out.push_str(&format!(
+                "{},{},{},{},{},{:.1},{}\n",
+                csv_field(&subject.subject),
+                csv_field(if label.is_empty() { "—" } else { label }),
+                item.total,
+                item.past,
+                item.total - item.past,
+                item.minutes as f64 / 60.0,
+                csv_field(&subject.teachers.join("; "))
+            ));
+        }
+    }
+
+    out
+}
@@ -0,0 +1,13 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+/// Расписание ИБИ ведётся по петербургскому времени, независимо от TZ машины или SSH-клиента
+pub const TIMEZONE: Tz = chrono_tz::Europe::Moscow;
+
+pub fn now() -> DateTime<Tz> {
+    Utc::now().with_timezone(&TIMEZONE)
+}
+
+pub fn today() -> NaiveDate {
+    now().date_naive()
+}
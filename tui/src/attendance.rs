@@ -0,0 +1,247 @@
+use confy::ConfyError;
+use cursive::{
+    Cursive,
+    theme::{BaseColor, ColorStyle, Effect},
+    utils::markup::StyledString,
+    view::{Nameable, Scrollable},
+    views::{Button, Dialog, LinearLayout, NamedView, TextView},
+};
+use lounge_parser::schedules::LessonKey;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::config;
+
+use rust_i18n::t;
+
+rust_i18n::i18n!();
+
+const ATTENDANCE_FILE: &str = "attendance";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum AttendanceMark {
+    Attended,
+    Missed,
+    Excused,
+}
+
+impl AttendanceMark {
+    /// unmarked -> attended -> missed -> excused -> unmarked
+    pub fn next(mark: Option<Self>) -> Option<Self> {
+        match mark {
+            None => Some(Self::Attended),
+            Some(Self::Attended) => Some(Self::Missed),
+            Some(Self::Missed) => Some(Self::Excused),
+            Some(Self::Excused) => None,
+        }
+    }
+
+    pub fn to_text(self) -> &'static str {
+        match self {
+            Self::Attended => "attended",
+            Self::Missed => "missed",
+            Self::Excused => "excused",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AttendanceRecord {
+    pub key: LessonKey,
+    pub mark: AttendanceMark,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Attendance {
+    #[serde(default)]
+    pub records: Vec<AttendanceRecord>,
+}
+
+#[derive(Default)]
+pub struct SubjectAttendance {
+    pub subject: String,
+    pub attended: usize,
+    pub missed: usize,
+    pub excused: usize,
+}
+
+impl Attendance {
+    pub fn get(&self, key: &LessonKey) -> Option<AttendanceMark> {
+        self.records
+            .iter()
+            .find(|record| &record.key == key)
+            .map(|record| record.mark)
+    }
+
+    pub fn set(&mut self, key: &LessonKey, mark: Option<AttendanceMark>) {
+        self.records.retain(|record| &record.key != key);
+        if let Some(mark) = mark {
+            self.records.push(AttendanceRecord {
+                key: key.clone(),
+                mark,
+            });
+        }
+    }
+
+    /// Only missed lessons count towards the limit, excused ones don't
+    pub fn missed(&self, subject: &str) -> usize {
+        self.records
+            .iter()
+            .filter(|record| record.key.subject == subject && record.mark == AttendanceMark::Missed)
+            .count()
+    }
+
+    pub fn by_subject(&self) -> Vec<SubjectAttendance> {
+        let mut result: Vec<SubjectAttendance> = vec![];
+
+        for record in &self.records {
+            let index = match result
+                .iter()
+                .position(|item| item.subject == record.key.subject)
+            {
+                Some(index) => index,
+                None => {
+                    result.push(SubjectAttendance {
+                        subject: record.key.subject.clone(),
+                        ..Default::default()
+                    });
+                    result.len() - 1
+                }
+            };
+
+            match record.mark {
+                AttendanceMark::Attended => result[index].attended += 1,
+                AttendanceMark::Missed => result[index].missed += 1,
+                AttendanceMark::Excused => result[index].excused += 1,
+            }
+        }
+
+        result.sort_by(|a, b| a.subject.cmp(&b.subject));
+        result
+    }
+}
+
+pub fn get_attendance() -> Result<Attendance, ConfyError> {
+    config::load_data(ATTENDANCE_FILE)
+}
+
+/// Loads the attendance, or reports why it couldn't be read
+fn attendance_or_error(s: &mut Cursive) -> Option<Attendance> {
+    get_attendance()
+        .map_err(|err| crate::data_error_dialog(s, ATTENDANCE_FILE, err))
+        .ok()
+}
+
+pub fn store_attendance(attendance: Attendance) -> Result<(), ConfyError> {
+    config::store_data(ATTENDANCE_FILE, attendance)
+}
+
+/// Red when the limit is reached, yellow one absence before it
+fn limit_color(missed: usize, limit: u8) -> ColorStyle {
+    let limit = limit as usize;
+
+    if limit == 0 {
+        ColorStyle::primary()
+    } else if missed >= limit {
+        ColorStyle::front(BaseColor::Red)
+    } else if missed + 1 >= limit {
+        ColorStyle::front(BaseColor::Yellow)
+    } else {
+        ColorStyle::primary()
+    }
+}
+
+fn attendance_label(mark: Option<AttendanceMark>) -> String {
+    match mark {
+        Some(mark) => t!("attendance.".to_owned() + mark.to_text()).to_string(),
+        None => t!("attendance.unmarked").to_string(),
+    }
+}
+
+fn attendance_warn(s: &mut Cursive, subject: &str, missed: usize) {
+    let limit = config::get_config()
+        .map(|cfg| cfg.absence_limit)
+        .unwrap_or(0);
+
+    if limit > 0 && missed + 1 >= limit as usize {
+        s.add_layer(Dialog::info(StyledString::styled(
+            t!(
+                "attendance.limit_warning",
+                subject = subject,
+                missed = missed,
+                limit = limit
+            ),
+            limit_color(missed, limit),
+        )));
+    }
+}
+
+/// Button for a past lesson that cycles its mark in place, without refetching the schedules
+pub fn attendance_button(key: LessonKey, mark: Option<AttendanceMark>) -> NamedView<Button> {
+    let name = format!("attendance-{}-{}-{}", key.date, key.slot, key.subject);
+    let button_name = name.clone();
+
+    Button::new(attendance_label(mark), move |s| {
+        let Some(mut attendance) = attendance_or_error(s) else {
+            return;
+        };
+        let mark = AttendanceMark::next(attendance.get(&key));
+        attendance.set(&key, mark);
+
+        let missed = attendance.missed(&key.subject);
+        if let Err(err) = store_attendance(attendance) {
+            s.add_layer(Dialog::info(t!("errors.attendance", e = err.to_string())));
+            return;
+        }
+
+        s.call_on_name(&button_name, |button: &mut Button| {
+            button.set_label(attendance_label(mark))
+        });
+
+        if mark == Some(AttendanceMark::Missed) {
+            attendance_warn(s, &key.subject, missed);
+        }
+    })
+    .with_name(name)
+}
+
+/// Per-subject counters, coloured against the absence limit
+pub fn attendance_view(s: &mut Cursive) {
+    let limit = config::get_config()
+        .map(|cfg| cfg.absence_limit)
+        .unwrap_or(0);
+    let Some(attendance) = attendance_or_error(s) else {
+        return;
+    };
+    let subjects = attendance.by_subject();
+    let mut list = LinearLayout::vertical();
+
+    if subjects.is_empty() {
+        list.add_child(TextView::new(t!("attendance.empty")));
+    }
+
+    for item in subjects {
+        let mut row = StyledString::styled(format!("{}\n", item.subject), Effect::Bold);
+        row.append_plain(format!(
+            "  {}: {}  {}: {}  ",
+            t!("attendance.attended"),
+            item.attended,
+            t!("attendance.excused"),
+            item.excused
+        ));
+        row.append_styled(
+            if limit > 0 {
+                format!("{}: {}/{}\n", t!("attendance.missed"), item.missed, limit)
+            } else {
+                format!("{}: {}\n", t!("attendance.missed"), item.missed)
+            },
+            limit_color(item.missed, limit),
+        );
+        list.add_child(TextView::new(row));
+    }
+
+    s.add_layer(
+        Dialog::around(list.scrollable())
+            .title(t!("attendance.title"))
+            .dismiss_button(t!("actions.close")),
+    );
+}
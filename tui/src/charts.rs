@@ -0,0 +1,167 @@
+use cursive::{
+    theme::{BaseColor, ColorStyle, Effect},
+    utils::markup::StyledString,
+    views::TextView,
+};
+use lounge_parser::grades::{GradesStats, SemesterStats};
+
+use rust_i18n::t;
+
+rust_i18n::i18n!();
+
+const SPARK: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const BAR_WIDTH: usize = 20;
+
+/// 2.0 maps to the lowest block and 5.0 to the highest, gaps for semesters without grades
+fn sparkline(values: &[Option<f64>]) -> String {
+    values
+        .iter()
+        .map(|value| match value {
+            Some(value) => {
+                let level = ((value - 2.0) / 3.0 * (SPARK.len() - 1) as f64).round();
+                SPARK[level.clamp(0.0, (SPARK.len() - 1) as f64) as usize]
+            }
+            None => ' ',
+        })
+        .collect()
+}
+
+/// Stacked bar scaled against `scale`. Segment ends are rounded from running totals,
+/// so the bar never gets wider than `BAR_WIDTH` and the counts after it stay aligned
+fn bar(text: &mut StyledString, parts: &[(usize, ColorStyle)], scale: usize) {
+    let scale = scale.max(1);
+    let (mut total, mut width) = (0, 0);
+
+    for (count, color) in parts {
+        total += count;
+        let end = ((total * BAR_WIDTH * 2 + scale) / (scale * 2)).min(BAR_WIDTH);
+        text.append_styled("█".repeat(end - width), *color);
+        width = end;
+    }
+
+    text.append_plain(" ".repeat(BAR_WIDTH - width));
+}
+
+fn count(stats: &SemesterStats, r#type: Option<&str>, grades: &[&str]) -> usize {
+    stats
+        .outcomes
+        .iter()
+        .filter(|outcome| r#type.is_none_or(|r#type| outcome.r#type == r#type))
+        .filter(|outcome| grades.contains(&outcome.grade.as_str()))
+        .map(|outcome| outcome.count)
+        .sum()
+}
+
+const FAILED: [&str; 3] = ["failed", "absence", "not_admitted"];
+
+/// Distribution of 5/4/3/2, credits passed vs failed and the average trend
+pub fn grades_charts_view(stats: &GradesStats) -> TextView {
+    let mut text = StyledString::new();
+    let grades = [
+        ("5", ColorStyle::front(BaseColor::Green.light())),
+        ("4", ColorStyle::front(BaseColor::Green.dark())),
+        ("3", ColorStyle::front(BaseColor::Yellow.light())),
+        ("2", ColorStyle::front(BaseColor::Red.light())),
+    ];
+    let semesters: Vec<(usize, &SemesterStats)> = stats
+        .semesters
+        .iter()
+        .enumerate()
+        .filter(|(_, semester)| !semester.outcomes.is_empty())
+        .collect();
+
+    if semesters.is_empty() {
+        return TextView::new(text);
+    }
+
+    text.append_styled(format!("{}\n", t!("charts.distribution")), Effect::Bold);
+    let scale = semesters
+        .iter()
+        .map(|(_, semester)| semester.graded)
+        .max()
+        .unwrap_or(0);
+    for (index, semester) in &semesters {
+        let parts: Vec<(usize, ColorStyle)> = grades
+            .iter()
+            .map(|(grade, color)| (count(semester, None, &[grade]), *color))
+            .collect();
+
+        text.append_plain(format!("{:>2} ", index + 1));
+        bar(&mut text, &parts, scale);
+        text.append_styled(
+            format!(
+                " {}\n",
+                parts
+                    .iter()
+                    .map(|(count, _)| count.to_string())
+                    .collect::<Vec<String>>()
+                    .join("/")
+            ),
+            ColorStyle::tertiary(),
+        );
+    }
+    text.append_styled(
+        format!("   {}\n", t!("charts.distribution_legend")),
+        ColorStyle::tertiary(),
+    );
+
+    let credits: Vec<(usize, usize, usize)> = semesters
+        .iter()
+        .map(|(index, semester)| {
+            (
+                *index,
+                count(semester, Some("subject_report"), &["passed"]),
+                count(semester, Some("subject_report"), &FAILED),
+            )
+        })
+        .filter(|(_, passed, failed)| passed + failed > 0)
+        .collect();
+    if !credits.is_empty() {
+        text.append_styled(format!("\n{}\n", t!("charts.credits")), Effect::Bold);
+        let scale = credits
+            .iter()
+            .map(|(_, passed, failed)| passed + failed)
+            .max()
+            .unwrap_or(0);
+        for (index, passed, failed) in credits {
+            text.append_plain(format!("{:>2} ", index + 1));
+            bar(
+                &mut text,
+                &[
+                    (passed, ColorStyle::front(BaseColor::Green.light())),
+                    (failed, ColorStyle::front(BaseColor::Red.light())),
+                ],
+                scale,
+            );
+            text.append_styled(
+                format!(" {}/{}\n", passed, passed + failed),
+                ColorStyle::tertiary(),
+            );
+        }
+    }
+
+    let averages: Vec<Option<f64>> = semesters
+        .iter()
+        .map(|(_, semester)| semester.average)
+        .collect();
+    if averages.iter().filter(|average| average.is_some()).count() > 1 {
+        text.append_styled(format!("\n{} ", t!("charts.trend")), Effect::Bold);
+        text.append_styled(
+            sparkline(&averages),
+            ColorStyle::front(BaseColor::Cyan.light()),
+        );
+        text.append_styled(
+            format!(
+                "  {}\n",
+                averages
+                    .iter()
+                    .map(|average| average.map_or("—".to_string(), |a| format!("{:.1}", a)))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            ColorStyle::tertiary(),
+        );
+    }
+
+    TextView::new(text)
+}
@@ -1,17 +1,39 @@
-use chrono::Utc;
 use confy::ConfyError;
+use lounge_parser::filters::LessonFilters;
+use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
 use std::{env, path::PathBuf};
 
+use crate::credentials::EncryptedPin;
+use crate::schedules::{SchedulesLayout, SchedulesSpan};
+
 #[derive(Serialize, Deserialize)]
 pub struct LoungeConfig {
     pub group_id: String,
     pub level_id: String,
+    /// Plain text PIN, only used with `pin_plain`
     pub pin: String,
     pub last_name: String,
     pub setup_passed: bool,
-    pub selected_date: i64,
     pub theme: u8,
+    #[serde(default)]
+    pub schedules_span: SchedulesSpan,
+    #[serde(default)]
+    pub schedules_layout: SchedulesLayout,
+    #[serde(default)]
+    pub filters: LessonFilters,
+    /// Paths to local .ics files shown on top of the schedules
+    #[serde(default)]
+    pub overlay_calendars: Vec<String>,
+    /// Missed lessons allowed per subject, 0 disables the warnings
+    #[serde(default)]
+    pub absence_limit: u8,
+    /// PIN encrypted under the user's passphrase, unlocked once per session
+    #[serde(default)]
+    pub pin_encrypted: Option<EncryptedPin>,
+    /// Explicit opt-in to keep `pin` in plain text
+    #[serde(default)]
+    pub pin_plain: bool,
 }
 
 impl ::std::default::Default for LoungeConfig {
@@ -22,8 +44,14 @@ impl ::std::default::Default for LoungeConfig {
             pin: "".to_string(),
             last_name: "".to_string(),
             setup_passed: false,
-            selected_date: Utc::now().timestamp(),
             theme: 0,
+            schedules_span: SchedulesSpan::default(),
+            schedules_layout: SchedulesLayout::default(),
+            filters: LessonFilters::default(),
+            overlay_calendars: vec![],
+            absence_limit: 0,
+            pin_encrypted: None,
+            pin_plain: false,
         }
     }
 }
@@ -57,3 +85,36 @@ pub fn get_store_path() -> Result<PathBuf, ConfyError> {
         Err(_e) => confy::get_configuration_file_path("lounge-tui", None),
     }
 }
+
+/// Local files (notes, events, exports, ...) live next to the config file
+/// and follow the same per-SSH-client naming.
+pub fn get_file_path(file_name: &str) -> Result<PathBuf, ConfyError> {
+    let config_path = get_store_path()?;
+    let stem = config_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    Ok(config_path.with_file_name(format!("{}-{}", stem, file_name)))
+}
+
+/// Writes an export next to the config and returns where it ended up
+pub fn write_file(file_name: &str, contents: &str) -> Result<PathBuf, String> {
+    let path = get_file_path(file_name).map_err(|err| err.to_string())?;
+    std::fs::write(&path, contents).map_err(|err| err.to_string())?;
+    Ok(path)
+}
+
+pub fn get_data_path(name: &str) -> Result<PathBuf, ConfyError> {
+    get_file_path(&format!("{}.toml", name))
+}
+
+pub fn load_data<T: serde::Serialize + DeserializeOwned + Default>(
+    name: &str,
+) -> Result<T, ConfyError> {
+    confy::load_path(get_data_path(name)?)
+}
+
+pub fn store_data<T: serde::Serialize>(name: &str, data: T) -> Result<(), ConfyError> {
+    confy::store_path(get_data_path(name)?, data)
+}
@@ -0,0 +1,296 @@
+use std::{
+    num::NonZeroU32,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use cursive::{
+    CbSink, Cursive,
+    view::{Nameable, Resizable},
+    views::{Dialog, EditView, LinearLayout, TextView},
+};
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{config, state};
+
+use rust_i18n::t;
+
+rust_i18n::i18n!();
+
+/// OWASP recommendation for PBKDF2-HMAC-SHA256
+const PBKDF2_ITERATIONS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+/// The unlocked PIN is forgotten after this long without opening anything grade related
+const SESSION_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+pub const MIN_PASSPHRASE_LEN: usize = 6;
+
+/// PIN encrypted with AES-256-GCM under a key derived from the user's passphrase
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EncryptedPin {
+    pub salt: String,
+    pub nonce: String,
+    pub data: String,
+}
+
+type OnUnlock = Arc<dyn Fn(&mut Cursive, String) + Send + Sync>;
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<LessSafeKey, String> {
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+
+    UnboundKey::new(&AES_256_GCM, &key)
+        .map(LessSafeKey::new)
+        .map_err(|_| t!("credentials.crypto_error").to_string())
+}
+
+pub fn encrypt_pin(pin: &str, passphrase: &str) -> Result<EncryptedPin, String> {
+    let random = SystemRandom::new();
+    let (mut salt, mut nonce) = ([0u8; SALT_LEN], [0u8; NONCE_LEN]);
+    random
+        .fill(&mut salt)
+        .and_then(|_| random.fill(&mut nonce))
+        .map_err(|_| t!("credentials.crypto_error").to_string())?;
+
+    let mut data = pin.as_bytes().to_vec();
+    derive_key(passphrase, &salt)?
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+        .map_err(|_| t!("credentials.crypto_error").to_string())?;
+
+    Ok(EncryptedPin {
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        data: STANDARD.encode(data),
+    })
+}
+
+/// Fails on a wrong passphrase as well as on a damaged config
+pub fn decrypt_pin(encrypted: &EncryptedPin, passphrase: &str) -> Result<String, String> {
+    let wrong = || t!("credentials.wrong_passphrase").to_string();
+    let salt = STANDARD.decode(&encrypted.salt).map_err(|_| wrong())?;
+    let nonce: [u8; NONCE_LEN] = STANDARD
+        .decode(&encrypted.nonce)
+        .ok()
+        .and_then(|nonce| nonce.try_into().ok())
+        .ok_or_else(wrong)?;
+    let mut data = STANDARD.decode(&encrypted.data).map_err(|_| wrong())?;
+
+    let pin = derive_key(passphrase, &salt)?
+        .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+        .map_err(|_| wrong())?;
+
+    String::from_utf8(pin.to_vec()).map_err(|_| wrong())
+}
+
+/// Checks a new passphrase and its confirmation, `Err` holds the message to show
+pub fn check_passphrase(passphrase: &str, repeat: &str) -> Result<(), String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(t!("credentials.too_short", n = MIN_PASSPHRASE_LEN).to_string());
+    }
+    if passphrase != repeat {
+        return Err(t!("credentials.not_equal").to_string());
+    }
+    Ok(())
+}
+
+/// Keeps the PIN in memory for the session, never on disk
+pub fn session_unlock(s: &mut Cursive, pin: String) {
+    let state = state::get_state(s);
+    state.pin = Some(pin);
+    state.pin_used_at = Some(Instant::now());
+    session_expire(s.cb_sink().clone(), SESSION_TIMEOUT);
+}
+
+/// Forgets the PIN once it hasn't been used for `SESSION_TIMEOUT`, checking again later
+/// if it was used in between
+fn session_expire(cb_sink: CbSink, after: Duration) {
+    thread::spawn(move || {
+        thread::sleep(after);
+        let next_sink = cb_sink.clone();
+        let _ = cb_sink.send(Box::new(move |s: &mut Cursive| {
+            let state = state::get_state(s);
+            let left = state
+                .pin_used_at
+                .and_then(|used_at| SESSION_TIMEOUT.checked_sub(used_at.elapsed()))
+                .filter(|left| !left.is_zero());
+            match left {
+                Some(left) => session_expire(next_sink, left),
+                None => {
+                    state.pin = None;
+                    state.pin_used_at = None;
+                }
+            }
+        }));
+    });
+}
+
+pub fn session_lock(s: &mut Cursive) {
+    let state = state::get_state(s);
+    state.pin = None;
+    state.pin_used_at = None;
+}
+
+fn session_pin(s: &mut Cursive) -> Option<String> {
+    let state = state::get_state(s);
+    let expired = state
+        .pin_used_at
+        .is_none_or(|used_at| used_at.elapsed() > SESSION_TIMEOUT);
+
+    if expired {
+        state.pin = None;
+        state.pin_used_at = None;
+        return None;
+    }
+
+    state.pin_used_at = Some(Instant::now());
+    state.pin.clone()
+}
+
+/// The PIN readable without a passphrase: only when plain storage was chosen explicitly
+pub fn stored_pin(cfg: &config::LoungeConfig) -> Option<String> {
+    (cfg.pin_encrypted.is_none() && cfg.pin_plain && !cfg.pin.is_empty()).then(|| cfg.pin.clone())
+}
+
+fn unlock_dialog(s: &mut Cursive, encrypted: EncryptedPin, on_unlock: OnUnlock) {
+    s.add_layer(
+        Dialog::around(
+            LinearLayout::vertical()
+                .child(TextView::new(t!("credentials.unlock_prompt")))
+                .child(
+                    EditView::new()
+                        .secret()
+                        .with_name("unlock-passphrase")
+                        .fixed_width(30),
+                ),
+        )
+        .title(t!("credentials.unlock_title"))
+        .button(t!("credentials.unlock"), move |s| {
+            let passphrase = s
+                .call_on_name("unlock-passphrase", |view: &mut EditView| {
+                    view.get_content().to_string()
+                })
+                .unwrap_or_default();
+
+            match decrypt_pin(&encrypted, &passphrase) {
+                Ok(pin) => {
+                    s.pop_layer();
+                    session_unlock(s, pin.clone());
+                    on_unlock(s, pin);
+                }
+                Err(err) => s.add_layer(Dialog::info(err)),
+            }
+        })
+        .dismiss_button(t!("actions.cancel")),
+    );
+}
+
+/// Saved in plain text by a version without encryption, and plain storage wasn't chosen
+fn is_legacy(cfg: &config::LoungeConfig) -> bool {
+    cfg.pin_encrypted.is_none() && !cfg.pin_plain && !cfg.pin.is_empty()
+}
+
+/// Asks to encrypt a legacy PIN or keep it plain on purpose. Cancelling changes nothing,
+/// the question comes back the next time grades are opened
+fn migrate_dialog(s: &mut Cursive, pin: String, on_unlock: OnUnlock) {
+    let plain_pin = pin.clone();
+    let plain_unlock = on_unlock.clone();
+
+    s.add_layer(
+        Dialog::around(
+            LinearLayout::vertical()
+                .child(TextView::new(t!("credentials.migrate_prompt")))
+                .child(TextView::new(t!("credentials.passphrase")))
+                .child(EditView::new().secret().with_name("migrate-passphrase"))
+                .child(TextView::new(t!("credentials.passphrase_repeat")))
+                .child(EditView::new().secret().with_name("migrate-repeat"))
+                .fixed_width(50),
+        )
+        .title(t!("credentials.migrate_title"))
+        .button(t!("credentials.encrypt"), move |s| {
+            let [passphrase, repeat] = ["migrate-passphrase", "migrate-repeat"].map(|name| {
+                s.call_on_name(name, |view: &mut EditView| view.get_content().to_string())
+                    .unwrap_or_default()
+            });
+            if let Err(err) = check_passphrase(&passphrase, &repeat) {
+                s.add_layer(Dialog::info(err));
+                return;
+            }
+
+            let result = encrypt_pin(&pin, &passphrase).and_then(|encrypted| {
+                let mut cfg = config::get_config().map_err(|err| err.to_string())?;
+                cfg.pin = String::new();
+                cfg.pin_encrypted = Some(encrypted);
+                cfg.pin_plain = false;
+                config::store_config(cfg).map_err(|err| err.to_string())
+            });
+
+            match result {
+                Ok(()) => {
+                    s.pop_layer();
+                    session_unlock(s, pin.clone());
+                    on_unlock(s, pin.clone());
+                }
+                Err(err) => s.add_layer(Dialog::info(t!("errors.some", e = err))),
+            }
+        })
+        .button(t!("credentials.keep_plain"), move |s| {
+            let result = config::get_config().and_then(|mut cfg| {
+                cfg.pin_plain = true;
+                config::store_config(cfg)
+            });
+
+            match result {
+                Ok(()) => {
+                    s.pop_layer();
+                    plain_unlock(s, plain_pin.clone());
+                }
+                Err(err) => s.add_layer(Dialog::info(t!("errors.some", e = err.to_string()))),
+            }
+        })
+        .dismiss_button(t!("actions.cancel")),
+    );
+}
+
+/// Runs `on_unlock` with the PIN, asking for the passphrase first if the session has none
+pub fn with_pin(s: &mut Cursive, on_unlock: impl Fn(&mut Cursive, String) + Send + Sync + 'static) {
+    let cfg = config::get_config().unwrap();
+
+    match cfg.pin_encrypted {
+        Some(encrypted) => match session_pin(s) {
+            Some(pin) => on_unlock(s, pin),
+            None => unlock_dialog(s, encrypted, Arc::new(on_unlock)),
+        },
+        // not set up yet, the grades page reports that by itself
+        None if is_legacy(&cfg) => migrate_dialog(s, cfg.pin, Arc::new(on_unlock)),
+        None => on_unlock(s, cfg.pin),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pin_round_trip() {
+        let encrypted = encrypt_pin("1234567", "correct horse").unwrap();
+        assert_eq!(decrypt_pin(&encrypted, "correct horse").unwrap(), "1234567");
+    }
+
+    #[test]
+    fn wrong_passphrase() {
+        let encrypted = encrypt_pin("1234567", "correct horse").unwrap();
+        assert!(decrypt_pin(&encrypted, "battery staple").is_err());
+    }
+}
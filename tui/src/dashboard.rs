@@ -0,0 +1,221 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{Days, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use cursive::{
+    Cursive,
+    theme::{ColorStyle, Effect},
+    utils::markup::StyledString,
+    views::TextView,
+};
+use lounge_parser::{
+    events::merge_events,
+    get_schedules,
+    schedules::{DayItem, LessonItem},
+    timezone,
+};
+use tokio::runtime::Runtime;
+
+use crate::{
+    config, events,
+    schedules::{schedules_additional_type_to_text, schedules_lesson_place_str},
+};
+
+use rust_i18n::t;
+
+rust_i18n::i18n!();
+
+const FETCH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+static DASHBOARD_STARTED: AtomicBool = AtomicBool::new(false);
+
+struct DashboardLesson<'a> {
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    lesson: &'a LessonItem,
+}
+
+fn dashboard_lessons(days: &[DayItem], date_from: NaiveDate) -> Vec<DashboardLesson<'_>> {
+    let mut result: Vec<DashboardLesson> = vec![];
+
+    for day in days {
+        let date = match day.date(date_from) {
+            Some(date) => date,
+            None => continue,
+        };
+
+        for lesson in &day.lessons {
+            if let (Ok(start), Ok(end)) = (
+                NaiveTime::parse_from_str(lesson.time_start.trim(), "%H:%M"),
+                NaiveTime::parse_from_str(lesson.time_end.trim(), "%H:%M"),
+            ) {
+                result.push(DashboardLesson {
+                    start: date.and_time(start),
+                    end: date.and_time(end),
+                    lesson,
+                });
+            }
+        }
+    }
+
+    result.sort_by_key(|item| item.start);
+    result
+}
+
+fn dashboard_duration_str(from: NaiveDateTime, to: NaiveDateTime) -> String {
+    let minutes = (to - from).num_minutes().max(0);
+
+    if minutes >= 24 * 60 {
+        t!("dashboard.days", d = minutes / (24 * 60)).to_string()
+    } else if minutes >= 60 {
+        t!(
+            "dashboard.hours_minutes",
+            h = minutes / 60,
+            m = minutes % 60
+        )
+        .to_string()
+    } else {
+        t!("dashboard.minutes", m = minutes).to_string()
+    }
+}
+
+fn dashboard_lesson_str(item: &DashboardLesson) -> String {
+    format!(
+        "{} {} ({})",
+        schedules_additional_type_to_text(&item.lesson.additional.r#type.to_text()),
+        item.lesson.text,
+        schedules_lesson_place_str(item.lesson)
+    )
+}
+
+fn dashboard_content(
+    result: &Result<Vec<DayItem>, String>,
+    date_from: NaiveDate,
+    now: NaiveDateTime,
+) -> StyledString {
+    let mut content = StyledString::new();
+
+    let days = match result {
+        Ok(days) => days,
+        Err(err) => {
+            content.append_plain(t!("errors.schedules", e = err));
+            return content;
+        }
+    };
+
+    let lessons = dashboard_lessons(days, date_from);
+    let current = lessons
+        .iter()
+        .find(|item| item.start <= now && now < item.end);
+    let upcoming: Vec<&DashboardLesson> = lessons.iter().filter(|item| item.start > now).collect();
+
+    content.append_styled(t!("dashboard.now"), Effect::Bold);
+    match current {
+        Some(item) => content.append_plain(format!(
+            "{}\n{}\n\n",
+            dashboard_lesson_str(item),
+            t!(
+                "dashboard.remaining",
+                time = dashboard_duration_str(now, item.end)
+            )
+        )),
+        None => content.append_plain(format!("{}\n\n", t!("dashboard.no_lesson"))),
+    }
+
+    content.append_styled(t!("dashboard.next"), Effect::Bold);
+    match upcoming.first() {
+        Some(item) => content.append_plain(format!(
+            "{}\n{}\n\n",
+            dashboard_lesson_str(item),
+            t!(
+                "dashboard.starts_in",
+                time = dashboard_duration_str(now, item.start),
+                at = item.start.format("%d.%m %H:%M")
+            )
+        )),
+        None => content.append_plain(format!("{}\n\n", t!("dashboard.nothing_ahead"))),
+    }
+
+    let today_rest: Vec<&&DashboardLesson> = upcoming
+        .iter()
+        .filter(|item| item.start.date() == now.date())
+        .collect();
+
+    if !today_rest.is_empty() {
+        content.append_styled(format!("{}\n", t!("dashboard.today_rest")), Effect::Bold);
+        for item in today_rest {
+            content.append_styled(
+                format!(
+                    "{}–{} ",
+                    item.start.format("%H:%M"),
+                    item.end.format("%H:%M")
+                ),
+                ColorStyle::tertiary(),
+            );
+            content.append_plain(format!("{}\n", dashboard_lesson_str(item)));
+        }
+    }
+
+    content
+}
+
+fn dashboard_fetch(rt: &Runtime, date_from: NaiveDate) -> Result<Vec<DayItem>, String> {
+    let cfg = match config::get_config() {
+        Ok(cfg) => cfg,
+        Err(err) => return Err(err.to_string()),
+    };
+    // a week ahead, so that "next" still works on evenings and weekends
+    let date_to = date_from + Days::new(7);
+
+    let mut days = rt.block_on(get_schedules(
+        &date_from.format("%d.%m.%Y").to_string(),
+        &date_to.format("%d.%m.%Y").to_string(),
+        &cfg.group_id,
+    ))?;
+    merge_events(&mut days, &events::all_events(&cfg), date_from, date_to);
+    cfg.filters.apply(&mut days);
+
+    Ok(days)
+}
+
+/// Starts the background thread that refreshes the dashboard on the main screen once a minute.
+pub fn dashboard_start(s: &mut Cursive) {
+    if DASHBOARD_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let cb_sink = s.cb_sink().clone();
+
+    thread::spawn(move || {
+        let rt = Runtime::new().unwrap();
+        let mut date_from = timezone::today();
+        let mut result = dashboard_fetch(&rt, date_from);
+        let mut fetched_at = Instant::now();
+
+        loop {
+            let now = timezone::now();
+
+            if fetched_at.elapsed() >= FETCH_INTERVAL || now.date_naive() != date_from {
+                date_from = now.date_naive();
+                result = dashboard_fetch(&rt, date_from);
+                fetched_at = Instant::now();
+            }
+
+            let content = dashboard_content(&result, date_from, now.naive_local());
+            let sent = cb_sink.send(Box::new(move |s: &mut Cursive| {
+                s.call_on_name("dashboard", |view: &mut TextView| {
+                    view.set_content(content);
+                });
+            }));
+
+            if sent.is_err() {
+                break;
+            }
+
+            thread::sleep(Duration::from_secs(60 - now.second() as u64));
+        }
+    });
+}
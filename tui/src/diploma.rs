@@ -0,0 +1,117 @@
+use cursive::{
+    Cursive,
+    theme::{ColorStyle, Effect},
+    utils::markup::StyledString,
+    view::Scrollable,
+    views::{Button, Dialog, LinearLayout, TextView},
+};
+use cursive_async_view::AsyncView;
+use lounge_parser::{
+    diploma::{SupplementEntry, diploma_supplement},
+    grades::GradeItem,
+};
+use tokio::runtime::Runtime;
+
+use crate::{config, credentials};
+
+use rust_i18n::t;
+
+rust_i18n::i18n!();
+
+fn diploma_export(s: &mut Cursive, contents: &str, file_name: &str) {
+    match config::write_file(file_name, contents) {
+        Ok(path) => s.add_layer(Dialog::info(t!(
+            "diploma.saved",
+            path = path.display().to_string()
+        ))),
+        Err(err) => s.add_layer(Dialog::info(t!("errors.some", e = err))),
+    }
+}
+
+fn section_text(text: &mut StyledString, title: String, entries: &[SupplementEntry]) {
+    text.append_styled(format!("{}\n", title), Effect::Bold);
+
+    if entries.is_empty() {
+        text.append_styled(
+            format!("  {}\n\n", t!("diploma.empty")),
+            ColorStyle::tertiary(),
+        );
+        return;
+    }
+
+    for (index, entry) in entries.iter().enumerate() {
+        text.append_plain(format!("{:>3}. {}\n", index + 1, entry.item.name.trim()));
+        text.append_styled(
+            format!(
+                "     {}, {}: {}\n",
+                t!("grades_type.".to_owned() + entry.item.r#type.to_string()),
+                t!("debts.semester", n = entry.semester),
+                t!("grades_grade.".to_owned() + entry.item.grade.to_string())
+            ),
+            ColorStyle::tertiary(),
+        );
+    }
+    text.append_plain("\n");
+}
+
+fn diploma_content(semesters: &[Vec<GradeItem>; 8]) -> LinearLayout {
+    let supplement = diploma_supplement(semesters);
+    let mut text = StyledString::new();
+
+    section_text(
+        &mut text,
+        t!("diploma.disciplines").to_string(),
+        &supplement.disciplines,
+    );
+    section_text(
+        &mut text,
+        t!("diploma.course_works").to_string(),
+        &supplement.course_works,
+    );
+    section_text(
+        &mut text,
+        t!("diploma.gov_exams").to_string(),
+        &supplement.gov_exams,
+    );
+
+    let markdown = supplement.to_markdown();
+    let csv = supplement.to_csv();
+
+    LinearLayout::vertical().child(TextView::new(text)).child(
+        LinearLayout::horizontal()
+            .child(Button::new(t!("diploma.export_markdown"), move |s| {
+                diploma_export(s, &markdown, "diploma.md")
+            }))
+            .child(TextView::new("  "))
+            .child(Button::new(t!("diploma.export_csv"), move |s| {
+                diploma_export(s, &csv, "diploma.csv")
+            })),
+    )
+}
+
+/// Final grades laid out like the diploma supplement, to spot mistakes before it gets printed
+pub fn diploma_view(s: &mut Cursive) {
+    credentials::with_pin(s, diploma_show);
+}
+
+fn diploma_show(s: &mut Cursive, pin: String) {
+    let cfg = config::get_config().unwrap();
+
+    let async_view = AsyncView::new_with_bg_creator(
+        s,
+        move || {
+            let rt = Runtime::new().unwrap();
+            Ok(rt.block_on(lounge_parser::get_grades(&pin, &cfg.last_name)))
+        },
+        move |result| match result {
+            Ok(semesters) => diploma_content(&semesters),
+            Err(err) => LinearLayout::vertical().child(TextView::new(t!("errors.grades", e = err))),
+        },
+    );
+
+    s.add_layer(
+        Dialog::around(async_view.with_width(60).scrollable())
+            .title(t!("diploma.title"))
+            .dismiss_button(t!("actions.close")),
+    );
+}
@@ -0,0 +1,284 @@
+use chrono::{NaiveDate, NaiveTime};
+use confy::ConfyError;
+use cursive::{
+    Cursive,
+    view::{Nameable, Resizable, Scrollable},
+    views::{Dialog, EditView, LayerPosition, LinearLayout, SelectView, TextView},
+};
+use lounge_parser::{
+    events::{PersonalEvent, Recurrence},
+    ical,
+};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{
+    config::{self, LoungeConfig},
+    schedules, state,
+};
+
+use rust_i18n::t;
+
+rust_i18n::i18n!();
+
+const EVENTS_FILE: &str = "events";
+
+const RECURRENCES: [Recurrence; 4] = [
+    Recurrence::Once,
+    Recurrence::Daily,
+    Recurrence::Weekly,
+    Recurrence::BiWeekly,
+];
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct PersonalEvents {
+    #[serde(default)]
+    pub events: Vec<PersonalEvent>,
+}
+
+pub fn get_events() -> Result<PersonalEvents, ConfyError> {
+    config::load_data(EVENTS_FILE)
+}
+
+/// Loads the events, or reports why they couldn't be read
+fn events_or_error(s: &mut Cursive) -> Option<PersonalEvents> {
+    get_events()
+        .map_err(|err| crate::data_error_dialog(s, EVENTS_FILE, err))
+        .ok()
+}
+
+pub fn store_events(events: PersonalEvents) -> Result<(), ConfyError> {
+    config::store_data(EVENTS_FILE, events)
+}
+
+pub fn read_overlay(path: &str) -> Result<ical::IcalEvents, String> {
+    let text = std::fs::read_to_string(path.trim()).map_err(|err| err.to_string())?;
+    Ok(ical::parse_ical_events(&text))
+}
+
+/// Personal events plus everything from the overlay calendars; unreadable files are skipped.
+/// A broken events file is reported once the events are opened.
+pub fn all_events(cfg: &LoungeConfig) -> Vec<PersonalEvent> {
+    let mut events = get_events().unwrap_or_default().events;
+    for path in &cfg.overlay_calendars {
+        if let Ok(overlay) = read_overlay(path) {
+            events.extend(overlay.events);
+        }
+    }
+    events
+}
+
+fn event_summary(event: &PersonalEvent) -> String {
+    let mut summary = format!(
+        "{} {}–{} {}",
+        event.date, event.time_start, event.time_end, event.title
+    );
+    if event.recurrence != Recurrence::Once {
+        summary.push_str(&format!(
+            " ({})",
+            t!("recurrence.".to_owned() + event.recurrence.to_text())
+        ));
+    }
+    summary
+}
+
+fn edit_content(s: &mut Cursive, name: &str) -> String {
+    s.call_on_name(name, |view: &mut EditView| {
+        view.get_content().trim().to_string()
+    })
+    .unwrap_or_default()
+}
+
+fn event_from_form(s: &mut Cursive) -> Result<PersonalEvent, String> {
+    let title = edit_content(s, "event-title");
+    let date = edit_content(s, "event-date");
+    let time_start = edit_content(s, "event-start");
+    let time_end = edit_content(s, "event-end");
+    let place = edit_content(s, "event-place");
+    let until = edit_content(s, "event-until");
+    let recurrence = s
+        .call_on_name("event-recurrence", |view: &mut SelectView<Recurrence>| {
+            view.selection()
+        })
+        .flatten()
+        .map(|recurrence| *recurrence)
+        .unwrap_or_default();
+
+    if title.is_empty() {
+        return Err(t!("events.invalid_title").to_string());
+    }
+    if NaiveDate::parse_from_str(&date, "%d.%m.%Y").is_err()
+        || (!until.is_empty() && NaiveDate::parse_from_str(&until, "%d.%m.%Y").is_err())
+    {
+        return Err(t!("events.invalid_date").to_string());
+    }
+    match (
+        NaiveTime::parse_from_str(&time_start, "%H:%M"),
+        NaiveTime::parse_from_str(&time_end, "%H:%M"),
+    ) {
+        (Ok(start), Ok(end)) if start < end => (),
+        _ => return Err(t!("events.invalid_time").to_string()),
+    }
+
+    Ok(PersonalEvent {
+        title,
+        date,
+        time_start,
+        time_end,
+        place: if place.is_empty() { None } else { Some(place) },
+        recurrence,
+        until: if until.is_empty() { None } else { Some(until) },
+        external: false,
+        exceptions: vec![],
+    })
+}
+
+fn events_store_and_close(s: &mut Cursive, events: PersonalEvents) {
+    match store_events(events) {
+        Ok(_) => {
+            // editor and the outdated list
+            s.pop_layer();
+            s.pop_layer();
+
+            // the schedules underneath show the events too
+            let screen = s.screen_mut();
+            if screen.find_layer_from_name("schedules")
+                == Some(LayerPosition::FromBack(screen.len().saturating_sub(1)))
+            {
+                schedules::schedules_reload(s);
+            }
+            events_view(s);
+        }
+        Err(err) => {
+            s.add_layer(Dialog::info(t!("errors.events", e = err.to_string())));
+        }
+    }
+}
+
+/// `index` is `None` for a new event
+fn event_editor(s: &mut Cursive, index: Option<usize>) {
+    let date = state::get_state(s).schedules_date;
+    let Some(events) = events_or_error(s) else {
+        return;
+    };
+    let event = index
+        .and_then(|index| events.events.get(index).cloned())
+        .unwrap_or(PersonalEvent {
+            title: String::new(),
+            date: date.format("%d.%m.%Y").to_string(),
+            time_start: String::new(),
+            time_end: String::new(),
+            place: None,
+            recurrence: Recurrence::Once,
+            until: None,
+            external: false,
+            exceptions: vec![],
+        });
+
+    let mut recurrence = SelectView::<Recurrence>::new().popup();
+    for item in RECURRENCES {
+        recurrence.add_item(t!("recurrence.".to_owned() + item.to_text()), item);
+    }
+    let selected = RECURRENCES
+        .iter()
+        .position(|item| *item == event.recurrence)
+        .unwrap_or(0);
+
+    let field = |label: String, name: &str, content: &str| {
+        LinearLayout::vertical()
+            .child(TextView::new(label))
+            .child(EditView::new().content(content).with_name(name))
+    };
+
+    let mut dialog = Dialog::around(
+        LinearLayout::vertical()
+            .child(field(
+                t!("events.name").to_string(),
+                "event-title",
+                &event.title,
+            ))
+            .child(field(
+                t!("events.date").to_string(),
+                "event-date",
+                &event.date,
+            ))
+            .child(field(
+                t!("events.start").to_string(),
+                "event-start",
+                &event.time_start,
+            ))
+            .child(field(
+                t!("events.end").to_string(),
+                "event-end",
+                &event.time_end,
+            ))
+            .child(field(
+                t!("events.place").to_string(),
+                "event-place",
+                &event.place.clone().unwrap_or_default(),
+            ))
+            .child(TextView::new(t!("events.recurrence")))
+            .child(recurrence.selected(selected).with_name("event-recurrence"))
+            .child(field(
+                t!("events.until").to_string(),
+                "event-until",
+                &event.until.clone().unwrap_or_default(),
+            ))
+            .fixed_width(40),
+    )
+    .title(t!("events.title"))
+    .button(t!("actions.apply"), move |s| match event_from_form(s) {
+        Ok(event) => {
+            let Some(mut events) = events_or_error(s) else {
+                return;
+            };
+            match index {
+                Some(index) if index < events.events.len() => events.events[index] = event,
+                _ => events.events.push(event),
+            }
+            events_store_and_close(s, events);
+        }
+        Err(err) => s.add_layer(Dialog::info(err)),
+    });
+
+    if let Some(index) = index {
+        dialog.add_button(t!("events.delete"), move |s| {
+            let Some(mut events) = events_or_error(s) else {
+                return;
+            };
+            if index < events.events.len() {
+                events.events.remove(index);
+            }
+            events_store_and_close(s, events);
+        });
+    }
+
+    s.add_layer(dialog.dismiss_button(t!("actions.cancel")));
+}
+
+pub fn events_view(s: &mut Cursive) {
+    let Some(events) = events_or_error(s) else {
+        return;
+    };
+    let mut list = SelectView::<usize>::new();
+
+    for (index, event) in events.events.iter().enumerate() {
+        list.add_item(event_summary(event), index);
+    }
+    list.set_on_submit(|s, index: &usize| event_editor(s, Some(*index)));
+
+    let content = LinearLayout::vertical()
+        .child(TextView::new(if events.events.is_empty() {
+            t!("events.empty")
+        } else {
+            t!("events.hint")
+        }))
+        .child(list.scrollable())
+        .fixed_width(50);
+
+    s.add_layer(
+        Dialog::around(content)
+            .title(t!("events.title"))
+            .button(t!("events.add"), |s| event_editor(s, None))
+            .dismiss_button(t!("actions.close")),
+    );
+}
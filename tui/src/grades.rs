@@ -1,16 +1,36 @@
-use cursive::Cursive;
-use cursive::theme::{BaseColor, ColorStyle, ColorType, PaletteStyle};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+use chrono::NaiveDate;
+use cursive::theme::{BaseColor, ColorStyle, ColorType, Effect, PaletteStyle};
+use cursive::utils::markup::StyledString;
 use cursive::view::Nameable;
+use cursive::{CbSink, Cursive, View};
 use cursive::{
     align::Align,
     view::{Margins, Resizable, Scrollable},
-    views::{Dialog, LinearLayout, NamedView, PaddedView, TextView},
+    views::{
+        Button, Dialog, HideableView, LinearLayout, NamedView, PaddedView, SelectView, TextView,
+    },
 };
 use cursive_async_view::AsyncView;
-use lounge_parser::grades::{GradeResult, GradeType};
+use lounge_parser::grades::{
+    Debt, Discipline, ExportFormat, GradeItem, GradeResult, GradeType, GradesStats, academic_debts,
+    export_grades, grades_stats, group_attempts, normalize_name,
+};
+use lounge_parser::{
+    get_schedules, matching::find_assessments, schedules::DayItem, scholarship::latest_semester,
+    stats::semester_bounds, timezone,
+};
 use tokio::runtime::Runtime;
 
-use crate::config;
+use crate::{charts, config, credentials, diploma, history, scholarship, state};
 
 use rust_i18n::t;
 
@@ -51,60 +71,620 @@ fn grade_grade_color(grade: &GradeResult) -> ColorStyle {
     }
 }
 
+fn grades_average_str(average: Option<f64>) -> String {
+    match average {
+        Some(average) => format!("{:.2}", average),
+        None => "—".to_string(),
+    }
+}
+
+fn grades_stats_view(stats: &GradesStats) -> TextView {
+    let mut summary = StyledString::new();
+
+    summary.append_styled(
+        t!(
+            "grades_stats.overall",
+            average = grades_average_str(stats.overall.average)
+        ),
+        Effect::Bold,
+    );
+    if let Some(share) = stats.overall.excellent_share() {
+        summary.append_plain(format!(
+            "  {}",
+            t!(
+                "grades_stats.excellent",
+                share = format!("{:.0}", share * 100.0)
+            )
+        ));
+    }
+    if let Some(trend) = stats.trend {
+        let (arrow, color) = if trend > 0.005 {
+            ("↑", ColorStyle::front(BaseColor::Green))
+        } else if trend < -0.005 {
+            ("↓", ColorStyle::front(BaseColor::Red))
+        } else {
+            ("→", ColorStyle::primary())
+        };
+        summary.append_plain(format!("  {} ", t!("grades_stats.trend")));
+        summary.append_styled(format!("{} {:+.2}", arrow, trend), color);
+    }
+    summary.append_plain("\n");
+
+    // 1: 4.50  2: 4.33 ...
+    let semesters = stats
+        .semesters
+        .iter()
+        .enumerate()
+        .filter(|(_, semester)| semester.average.is_some())
+        .map(|(index, semester)| format!("{}: {}", index + 1, grades_average_str(semester.average)))
+        .collect::<Vec<String>>();
+    if !semesters.is_empty() {
+        summary.append_styled(
+            format!(
+                "{} {}\n",
+                t!("grades_stats.by_semester"),
+                semesters.join("  ")
+            ),
+            ColorStyle::tertiary(),
+        );
+    }
+
+    // Exam: 5×3 4×2
+    let mut types: Vec<&str> = vec![];
+    for outcome in &stats.overall.outcomes {
+        if !types.contains(&outcome.r#type.as_str()) {
+            types.push(&outcome.r#type);
+        }
+    }
+    for type_key in types {
+        let counts = stats
+            .overall
+            .outcomes
+            .iter()
+            .filter(|outcome| outcome.r#type == type_key)
+            .map(|outcome| {
+                format!(
+                    "{}×{}",
+                    t!("grades_grade.".to_owned() + &outcome.grade),
+                    outcome.count
+                )
+            })
+            .collect::<Vec<String>>();
+        summary.append_plain(format!(
+            "{}: {}\n",
+            t!("grades_type.".to_owned() + type_key),
+            counts.join(" ")
+        ));
+    }
+
+    TextView::new(summary)
+}
+
+static GRADES_BADGE_STARTED: AtomicBool = AtomicBool::new(false);
+
+pub fn grades_menu_label(debts: Option<usize>) -> String {
+    match debts {
+        Some(debts) if debts > 0 => format!(
+            "[F2] {} ({} {})",
+            t!("sections.grades"),
+            debts,
+            t!("debts.badge")
+        ),
+        _ => format!("[F2] {}", t!("sections.grades")),
+    }
+}
+
+pub fn grades_open(s: &mut Cursive) {
+    credentials::with_pin(s, |s, pin| {
+        let grades_view = grades_view(s, pin);
+        s.set_autohide_menu(true);
+        s.add_layer(grades_view);
+    });
+}
+
+fn grades_badge_set(s: &mut Cursive, debts: Option<usize>) {
+    let old_label = grades_menu_label(state::get_state(s).debts);
+    state::get_state(s).debts = debts;
+
+    if let Some(position) = s.menubar().find_position(&old_label) {
+        s.menubar().remove(position);
+        s.menubar()
+            .insert_leaf(position, grades_menu_label(debts), grades_open);
+    }
+}
+
+fn grades_badge_update(s: &mut Cursive, debts: usize, semesters: Arc<[Vec<GradeItem>; 8]>) {
+    grades_badge_set(s, Some(debts));
+    state::get_state(s).grades = Some(semesters);
+}
+
+/// Drops the badge and the cached grades, e.g. when the credentials change
+pub fn grades_badge_reset(s: &mut Cursive) {
+    grades_badge_set(s, None);
+    state::get_state(s).grades = None;
+    GRADES_BADGE_STARTED.store(false, Ordering::SeqCst);
+}
+
+/// Updates the debts badge and keeps the grades for the schedules view
+fn grades_badge_send(cb_sink: &CbSink, semesters: Arc<[Vec<GradeItem>; 8]>) {
+    let debts = academic_debts(&semesters).len();
+    let _ = cb_sink.send(Box::new(move |s: &mut Cursive| {
+        grades_badge_update(s, debts, semesters)
+    }));
+}
+
+/// Fetches grades once in the background so the menubar shows the debts count right away
+pub fn grades_badge_start(s: &mut Cursive) {
+    let cfg = match config::get_config() {
+        Ok(cfg) => cfg,
+        Err(_) => return,
+    };
+
+    // an encrypted PIN has to be unlocked first and a legacy one migrated when the grades
+    // view opens, the badge then comes with the grades
+    if let Some(pin) = credentials::stored_pin(&cfg) {
+        grades_badge_fetch(s, pin);
+    }
+}
+
+pub fn grades_badge_fetch(s: &mut Cursive, pin: String) {
+    let last_name = match config::get_config() {
+        Ok(cfg) => cfg.last_name,
+        Err(_) => return,
+    };
+    if GRADES_BADGE_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let cb_sink = s.cb_sink().clone();
+    thread::spawn(move || {
+        let rt = Runtime::new().unwrap();
+        if let Ok(semesters) = rt.block_on(lounge_parser::get_grades(&pin, &last_name)) {
+            let semesters = Arc::new(semesters);
+            grades_badge_send(&cb_sink, semesters.clone());
+
+            let _ = cb_sink.send(Box::new(move |s: &mut Cursive| {
+                history::history_record(s, &semesters)
+            }));
+        }
+    });
+}
+
+fn debts_view(debts: &[Debt]) -> TextView {
+    let mut text = StyledString::new();
+
+    if debts.is_empty() {
+        text.append_styled(
+            format!("{}\n", t!("debts.none")),
+            ColorStyle::front(BaseColor::Green),
+        );
+        return TextView::new(text);
+    }
+
+    text.append_styled(
+        format!("{}\n", t!("debts.title", count = debts.len())),
+        ColorStyle::front(BaseColor::Red),
+    );
+    for debt in debts {
+        text.append_plain(format!("■ {}\n", debt.item.name));
+        text.append_styled(
+            format!(
+                "  {}, {}, {}\n",
+                t!("debts.semester", n = debt.semester),
+                grade_type_to_string(&debt.item.r#type),
+                grade_grade_to_string(&debt.item.grade)
+            ),
+            ColorStyle::tertiary(),
+        );
+    }
+
+    TextView::new(text)
+}
+
+fn toggle_arrow(expanded: bool) -> &'static str {
+    if expanded { "▾" } else { "▸" }
+}
+
+/// Button that shows or hides the `HideableView<V>` named `name` and flips its own arrow
+fn toggle_button<V: View>(
+    name: &str,
+    label: impl Fn(bool) -> String + Send + Sync + 'static,
+    expanded: bool,
+) -> NamedView<Button> {
+    let view_name = name.to_owned();
+    let button_name = format!("{}-toggle", name);
+    let toggle_name = button_name.clone();
+
+    Button::new_raw(label(expanded), move |s| {
+        let expanded = s
+            .call_on_name(&view_name, |view: &mut HideableView<V>| {
+                view.set_visible(!view.is_visible());
+                view.is_visible()
+            })
+            .unwrap_or(false);
+        s.call_on_name(&toggle_name, |button: &mut Button| {
+            button.set_label_raw(label(expanded))
+        });
+    })
+    .with_name(button_name)
+}
+
+/// Collapsed by default, expands the list of attempts under the discipline name
+fn attempts_toggle(name: &str, count: usize) -> NamedView<Button> {
+    toggle_button::<TextView>(
+        name,
+        move |expanded| {
+            format!(
+                "{} {}",
+                toggle_arrow(expanded),
+                t!("retakes.attempts", count = count)
+            )
+        },
+        false,
+    )
+}
+
+fn attempts_view(name: &str, discipline: &Discipline) -> NamedView<HideableView<TextView>> {
+    let mut text = StyledString::new();
+
+    for attempt in &discipline.attempts {
+        text.append_styled(
+            format!("  {}. ", t!("retakes.attempt", n = attempt.number)),
+            ColorStyle::tertiary(),
+        );
+        text.append_styled(
+            format!(" {} ", grade_grade_to_string(&attempt.item.grade)),
+            grade_grade_color(&attempt.item.grade),
+        );
+        text.append_plain("\n");
+    }
+
+    HideableView::new(TextView::new(text))
+        .hidden()
+        .with_name(name)
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum GradesSort {
+    /// As listed on the site
+    #[default]
+    Table,
+    Name,
+    Grade,
+    Type,
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum GradesFilter {
+    #[default]
+    All,
+    Exams,
+    /// Failed, pending or a 3
+    Problems,
+}
+
+/// Worst first: failed, pending, then by value
+fn grade_rank(grade: &GradeResult) -> u8 {
+    match grade {
+        GradeResult::Unknown => 1,
+        GradeResult::Passed => 5,
+        grade if grade.is_failing() => 0,
+        grade => grade.value().unwrap_or(0),
+    }
+}
+
+fn discipline_matches(discipline: &Discipline, filter: GradesFilter) -> bool {
+    match filter {
+        GradesFilter::All => true,
+        GradesFilter::Exams => matches!(discipline.r#type(), GradeType::Exam | GradeType::GovExam),
+        GradesFilter::Problems => {
+            matches!(
+                discipline.final_grade(),
+                GradeResult::Unknown | GradeResult::Three
+            ) || discipline.final_grade().is_failing()
+        }
+    }
+}
+
+fn grade_row(name: StyledString, attempts_name: &str, discipline: &Discipline) -> LinearLayout {
+    let grade = discipline.final_item();
+
+    let mut name_column = LinearLayout::vertical().child(TextView::new(name));
+    if discipline.is_retaken() {
+        name_column.add_child(attempts_toggle(attempts_name, discipline.attempts.len()));
+        name_column.add_child(attempts_view(attempts_name, discipline));
+    }
+
+    LinearLayout::horizontal()
+        .child(name_column.full_width().max_width(30))
+        .child(
+            LinearLayout::vertical()
+                .child(
+                    TextView::new(format!("{: ^7}", &grade_grade_to_string(&grade.grade)))
+                        .align(Align::top_right())
+                        .style(grade_grade_color(&grade.grade)),
+                )
+                .child(
+                    TextView::new(grade_type_to_string(&grade.r#type))
+                        .style(PaletteStyle::Tertiary)
+                        .align(Align::top_right())
+                        .min_width(7)
+                        .full_width()
+                        .max_width(19),
+                )
+                .child(TextView::new(" ")),
+        )
+}
+
+fn semester_label(semester: usize, count: usize, expanded: bool) -> String {
+    format!(
+        "{} {}",
+        toggle_arrow(expanded),
+        t!("grades_list.semester", n = semester, count = count)
+    )
+}
+
+struct GradesData {
+    semesters: Arc<[Vec<GradeItem>; 8]>,
+    /// When exams and credits of the latest semester are scheduled, by (semester, table row).
+    /// Empty until `grades_schedule_start` has fetched the schedules
+    scheduled: Mutex<HashMap<(usize, usize), String>>,
+}
+
+fn grades_scheduled(
+    semesters: &[Vec<GradeItem>; 8],
+    days: &[DayItem],
+    date_from: NaiveDate,
+) -> HashMap<(usize, usize), String> {
+    let mut scheduled = HashMap::new();
+    let Some(index) = latest_semester(semesters) else {
+        return scheduled;
+    };
+    let today = timezone::today();
+
+    for (row, item) in semesters[index].iter().enumerate() {
+        let found = find_assessments(days, date_from, item);
+        // the next one, or the last one if it's already over
+        let lesson = found
+            .iter()
+            .find(|(date, _)| *date >= today)
+            .or(found.last());
+
+        if let Some((date, lesson)) = lesson {
+            scheduled.insert(
+                (index, row),
+                format!("{} {}", date.format("%d.%m"), lesson.time_start.trim()),
+            );
+        }
+    }
+
+    scheduled
+}
+
+/// The current semester's schedules tell when the exams and credits are. They take a while
+/// to load, so the list is shown without them first and redrawn once they arrive
+fn grades_schedule_start(cb_sink: CbSink, data: Arc<GradesData>, group_id: String) {
+    thread::spawn(move || {
+        let (date_from, date_to) = semester_bounds(timezone::today());
+        let rt = Runtime::new().unwrap();
+        let Ok(days) = rt.block_on(get_schedules(
+            &date_from.format("%d.%m.%Y").to_string(),
+            &date_to.format("%d.%m.%Y").to_string(),
+            &group_id,
+        )) else {
+            return;
+        };
+
+        *data.scheduled.lock().unwrap() = grades_scheduled(&data.semesters, &days, date_from);
+        let _ = cb_sink.send(Box::new(move |s: &mut Cursive| {
+            // the dialog may have been closed and opened again with newer grades meanwhile
+            let current = state::get_state(s)
+                .grades
+                .as_ref()
+                .is_some_and(|grades| Arc::ptr_eq(grades, &data.semesters));
+            if current {
+                grades_refresh(s, &data);
+            }
+        }));
+    });
+}
+
+/// One collapsible section per non-empty semester
+fn semesters_view(
+    data: &GradesData,
+    sort: GradesSort,
+    filter: GradesFilter,
+    open: [bool; 8],
+) -> LinearLayout {
+    // Only for the "new" marks, a broken history file is reported by `history_record`
+    let history = history::get_history().unwrap_or_default();
+    let now = timezone::now().naive_local();
+    let scheduled = data.scheduled.lock().unwrap();
+    let mut semester_list = LinearLayout::vertical();
+
+    for (index, semester) in data.semesters.iter().enumerate() {
+        if semester.is_empty() {
+            continue;
+        }
+
+        let mut disciplines: Vec<(usize, Discipline)> = group_attempts(index + 1, semester)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, discipline)| discipline_matches(discipline, filter))
+            .collect();
+
+        match sort {
+            GradesSort::Table => {}
+            GradesSort::Name => {
+                disciplines.sort_by_key(|(_, discipline)| normalize_name(discipline.name()))
+            }
+            GradesSort::Grade => disciplines.sort_by_key(|(_, discipline)| {
+                (
+                    grade_rank(discipline.final_grade()),
+                    normalize_name(discipline.name()),
+                )
+            }),
+            GradesSort::Type => disciplines.sort_by_key(|(_, discipline)| {
+                (
+                    grade_type_to_string(discipline.r#type()),
+                    normalize_name(discipline.name()),
+                )
+            }),
+        }
+
+        let mut grade_list = LinearLayout::vertical();
+        if disciplines.is_empty() {
+            grade_list
+                .add_child(TextView::new(t!("grades_list.nothing")).style(PaletteStyle::Tertiary));
+        }
+
+        for (position, discipline) in &disciplines {
+            let mut name = StyledString::plain("■ ".to_owned() + discipline.name());
+            if discipline.attempts.iter().any(|attempt| {
+                history
+                    .find(index + 1, attempt)
+                    .is_some_and(|record| record.is_new(now))
+            }) {
+                name.append_styled(
+                    format!(" {}", t!("history.new")),
+                    ColorStyle::front(BaseColor::Green),
+                );
+            }
+            if let Some(when) = discipline
+                .attempts
+                .iter()
+                .rev()
+                .find_map(|attempt| scheduled.get(&(index, attempt.index)))
+            {
+                name.append_styled(
+                    format!("\n  {}", t!("matching.scheduled", when = when)),
+                    ColorStyle::tertiary(),
+                );
+            }
+
+            grade_list.add_child(grade_row(
+                name,
+                &format!("attempts-{}-{}", index + 1, position),
+                discipline,
+            ));
+        }
+
+        let count = disciplines.len();
+        semester_list.add_child(PaddedView::new(
+            Margins::tb(1, 0),
+            toggle_button::<LinearLayout>(
+                &format!("semester-{}", index + 1),
+                move |expanded| semester_label(index + 1, count, expanded),
+                open[index],
+            ),
+        ));
+
+        let mut section = HideableView::new(grade_list);
+        section.set_visible(open[index]);
+        semester_list.add_child(section.with_name(format!("semester-{}", index + 1)));
+    }
+
+    semester_list
+}
+
+/// Keeps whatever sections the user has opened when the list is rebuilt
+fn open_semesters(s: &mut Cursive) -> [bool; 8] {
+    let mut open = [false; 8];
+
+    for (index, open) in open.iter_mut().enumerate() {
+        *open = s
+            .call_on_name(
+                &format!("semester-{}", index + 1),
+                |view: &mut HideableView<LinearLayout>| view.is_visible(),
+            )
+            .unwrap_or(false);
+    }
+
+    open
+}
+
+fn grades_refresh(s: &mut Cursive, data: &GradesData) {
+    let open = open_semesters(s);
+    let (sort, filter) = {
+        let state = state::get_state(s);
+        (state.grades_sort, state.grades_filter)
+    };
+
+    s.call_on_name("grades-semesters", |layout: &mut LinearLayout| {
+        *layout = semesters_view(data, sort, filter, open)
+    });
+}
+
+fn grades_controls(data: Arc<GradesData>, sort: GradesSort, filter: GradesFilter) -> LinearLayout {
+    let sort_data = data.clone();
+    let sort_select = SelectView::new()
+        .popup()
+        .item(t!("grades_list.sort_table"), GradesSort::Table)
+        .item(t!("grades_list.sort_name"), GradesSort::Name)
+        .item(t!("grades_list.sort_grade"), GradesSort::Grade)
+        .item(t!("grades_list.sort_type"), GradesSort::Type)
+        .selected(sort as usize)
+        .on_submit(move |s, sort: &GradesSort| {
+            state::get_state(s).grades_sort = *sort;
+            grades_refresh(s, &sort_data);
+        });
+
+    let filter_select = SelectView::new()
+        .popup()
+        .item(t!("grades_list.filter_all"), GradesFilter::All)
+        .item(t!("grades_list.filter_exams"), GradesFilter::Exams)
+        .item(t!("grades_list.filter_problems"), GradesFilter::Problems)
+        .selected(filter as usize)
+        .on_submit(move |s, filter: &GradesFilter| {
+            state::get_state(s).grades_filter = *filter;
+            grades_refresh(s, &data);
+        });
+
+    LinearLayout::vertical()
+        .child(
+            LinearLayout::horizontal()
+                .child(TextView::new(format!("{} ", t!("grades_list.sort"))))
+                .child(sort_select),
+        )
+        .child(
+            LinearLayout::horizontal()
+                .child(TextView::new(format!("{} ", t!("grades_list.filter"))))
+                .child(filter_select),
+        )
+}
+
 fn semester_list_view(
-    result: Result<[Vec<lounge_parser::grades::GradeItem>; 8], String>,
+    result: Result<Arc<GradesData>, String>,
+    sort: GradesSort,
+    filter: GradesFilter,
 ) -> LinearLayout {
     let mut semester_list = LinearLayout::vertical();
 
     match result {
-        Ok(semesters) => {
-            for (index, semester) in semesters.iter().enumerate() {
-                let mut grade_list = LinearLayout::vertical();
-
-                for grade in semester {
-                    let grade_item = LinearLayout::horizontal()
-                        .child(
-                            TextView::new("■ ".to_owned() + &grade.name)
-                                .full_width()
-                                .max_width(30),
-                        )
-                        .child(
-                            LinearLayout::vertical()
-                                .child(
-                                    TextView::new(format!(
-                                        "{: ^7}",
-                                        &grade_grade_to_string(&grade.grade)
-                                    ))
-                                    .align(Align::top_right())
-                                    .style(grade_grade_color(&grade.grade)),
-                                )
-                                .child(
-                                    TextView::new(grade_type_to_string(&grade.r#type))
-                                        .style(PaletteStyle::Tertiary)
-                                        .align(Align::top_right())
-                                        .min_width(7)
-                                        .full_width()
-                                        .max_width(19),
-                                )
-                                .child(TextView::new(" ")),
-                        );
-
-                    grade_list.add_child(grade_item);
-                }
-                semester_list.add_child(PaddedView::new(
-                    Margins {
-                        left: 0,
-                        right: 0,
-                        top: 1,
-                        bottom: 1,
-                    },
-                    TextView::new(
-                        "===> Семестр №".to_owned() + &(index + 1).to_string() + "<===\n",
-                    )
-                    .align(Align::center()),
-                ));
-                semester_list.add_child(grade_list);
+        Ok(data) => {
+            let semesters = &data.semesters;
+            let stats = grades_stats(semesters);
+            semester_list.add_child(grades_stats_view(&stats));
+            semester_list.add_child(PaddedView::new(
+                Margins::tb(1, 0),
+                charts::grades_charts_view(&stats),
+            ));
+            semester_list.add_child(PaddedView::new(
+                Margins::tb(1, 0),
+                debts_view(&academic_debts(semesters)),
+            ));
+
+            let mut open = [false; 8];
+            if let Some(index) = latest_semester(semesters) {
+                open[index] = true;
             }
+
+            semester_list.add_child(PaddedView::new(
+                Margins::tb(1, 0),
+                grades_controls(data.clone(), sort, filter),
+            ));
+            semester_list
+                .add_child(semesters_view(&data, sort, filter, open).with_name("grades-semesters"));
         }
         Err(err) => {
             semester_list.add_child(TextView::new(t!("errors.grades", e = err)));
@@ -114,18 +694,87 @@ fn semester_list_view(
     semester_list
 }
 
-pub fn grades_view(siv: &mut Cursive) -> NamedView<Dialog> {
+fn grades_export_to(s: &mut Cursive, format: ExportFormat) {
+    s.pop_layer();
+    credentials::with_pin(s, move |s, pin| grades_export_run(s, pin, format));
+}
+
+fn grades_export_run(s: &mut Cursive, pin: String, format: ExportFormat) {
     let cfg = config::get_config().unwrap();
-    let (pin, last_name) = (cfg.pin, cfg.last_name);
+
+    let async_view = AsyncView::new_with_bg_creator(
+        s,
+        move || {
+            let rt = Runtime::new().unwrap();
+            let result = rt
+                .block_on(lounge_parser::get_grades(&pin, &cfg.last_name))
+                .and_then(|semesters| {
+                    config::write_file(
+                        &format!("grades.{}", format.extension()),
+                        &export_grades(&semesters, format),
+                    )
+                });
+
+            Ok(result)
+        },
+        |result| match result {
+            Ok(path) => TextView::new(t!("grades_export.saved", path = path.display().to_string())),
+            Err(err) => TextView::new(t!("errors.some", e = err)),
+        },
+    );
+
+    s.add_layer(
+        Dialog::around(async_view.with_width(40))
+            .title(t!("grades_export.title"))
+            .dismiss_button(t!("actions.close")),
+    );
+}
+
+fn grades_export(s: &mut Cursive) {
+    s.add_layer(
+        Dialog::text(t!("grades_export.choose"))
+            .title(t!("grades_export.title"))
+            .button("CSV", |s| grades_export_to(s, ExportFormat::Csv))
+            .button("JSON", |s| grades_export_to(s, ExportFormat::Json))
+            .button("Markdown", |s| grades_export_to(s, ExportFormat::Markdown))
+            .dismiss_button(t!("actions.close")),
+    );
+}
+
+pub fn grades_view(siv: &mut Cursive, pin: String) -> NamedView<Dialog> {
+    let cfg = config::get_config().unwrap();
+    let (last_name, group_id) = (cfg.last_name, cfg.group_id);
+    let cb_sink = siv.cb_sink().clone();
+    let (sort, filter) = {
+        let state = state::get_state(siv);
+        (state.grades_sort, state.grades_filter)
+    };
 
     let semester_list_view = AsyncView::new_with_bg_creator(
         siv,
         move || {
             let rt = Runtime::new().unwrap();
-            let grades_result = rt.block_on(lounge_parser::get_grades(&pin, &last_name));
-            Ok(grades_result)
+            let result = rt
+                .block_on(lounge_parser::get_grades(&pin, &last_name))
+                .map(|semesters| {
+                    let semesters = Arc::new(semesters);
+                    grades_badge_send(&cb_sink, semesters.clone());
+                    let recorded = semesters.clone();
+                    let _ = cb_sink.send(Box::new(move |s: &mut Cursive| {
+                        history::history_record(s, &recorded)
+                    }));
+
+                    let data = Arc::new(GradesData {
+                        semesters,
+                        scheduled: Mutex::new(HashMap::new()),
+                    });
+                    grades_schedule_start(cb_sink.clone(), data.clone(), group_id.clone());
+                    data
+                });
+
+            Ok(result)
         },
-        semester_list_view,
+        move |result| semester_list_view(result, sort, filter),
     ); // create 
 
     Dialog::around(PaddedView::new(
@@ -138,6 +787,10 @@ pub fn grades_view(siv: &mut Cursive) -> NamedView<Dialog> {
         semester_list_view.with_width(40).scrollable(),
     ))
     .title(t!("sections.grades"))
+    .button(t!("history.title"), history::history_view)
+    .button(t!("grades_export.title"), grades_export)
+    .button(t!("scholarship.title"), scholarship::scholarship_view)
+    .button(t!("diploma.title"), diploma::diploma_view)
     .button(t!("actions.close"), |s| {
         s.set_autohide_menu(false);
         s.pop_layer();
@@ -0,0 +1,279 @@
+use chrono::{NaiveDateTime, TimeDelta};
+use confy::ConfyError;
+use cursive::{
+    Cursive,
+    theme::{BaseColor, ColorStyle, Effect},
+    utils::markup::StyledString,
+    view::Scrollable,
+    views::{Dialog, TextView},
+};
+use lounge_parser::{
+    grades::{Attempt, GradeItem, disciplines, normalize_name},
+    timezone,
+};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::config;
+
+use rust_i18n::t;
+
+rust_i18n::i18n!();
+
+const HISTORY_FILE: &str = "grades-history";
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+/// Grades published or changed within this period get the "new" badge
+const NEW_DAYS: i64 = 7;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GradeChange {
+    pub at: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// One row of a grade table. Retakes are told apart by `attempt`, grouped like `group_attempts`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GradeRecord {
+    pub semester: usize,
+    pub name: String,
+    pub r#type: String,
+    #[serde(default)]
+    pub attempt: usize,
+    pub grade: String,
+    pub first_seen: String,
+    /// Already there on the very first fetch, so the real publication date is unknown
+    #[serde(default)]
+    pub baseline: bool,
+    #[serde(default)]
+    pub changes: Vec<GradeChange>,
+}
+
+impl GradeRecord {
+    fn is_recent(time: &str, now: NaiveDateTime) -> bool {
+        NaiveDateTime::parse_from_str(time, TIME_FORMAT)
+            .is_ok_and(|time| now - time < TimeDelta::days(NEW_DAYS))
+    }
+
+    pub fn is_new(&self, now: NaiveDateTime) -> bool {
+        (!self.baseline && Self::is_recent(&self.first_seen, now))
+            || self
+                .changes
+                .last()
+                .is_some_and(|change| Self::is_recent(&change.at, now))
+    }
+}
+
+/// A grade that appeared or changed since the previous fetch
+pub struct GradeUpdate {
+    pub semester: usize,
+    pub name: String,
+    pub grade: String,
+    pub previous: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct GradeHistory {
+    #[serde(default)]
+    pub records: Vec<GradeRecord>,
+}
+
+impl GradeHistory {
+    /// Records keep the number of earlier attempts, 0 for the first one
+    fn matches(record: &GradeRecord, semester: usize, attempt: &Attempt) -> bool {
+        record.semester == semester
+            && normalize_name(&record.name) == normalize_name(&attempt.item.name)
+            && record.r#type == attempt.item.r#type.to_string()
+            && record.attempt == attempt.number - 1
+    }
+
+    pub fn find(&self, semester: usize, attempt: &Attempt) -> Option<&GradeRecord> {
+        self.records
+            .iter()
+            .find(|record| Self::matches(record, semester, attempt))
+    }
+
+    pub fn update(
+        &mut self,
+        semesters: &[Vec<GradeItem>; 8],
+        now: NaiveDateTime,
+    ) -> Vec<GradeUpdate> {
+        let baseline = self.records.is_empty();
+        let at = now.format(TIME_FORMAT).to_string();
+        let mut updates: Vec<GradeUpdate> = vec![];
+
+        for discipline in disciplines(semesters).iter().flatten() {
+            for attempt in &discipline.attempts {
+                let (semester, item) = (discipline.semester, attempt.item);
+                let grade = item.grade.to_string().to_string();
+                let position = self
+                    .records
+                    .iter()
+                    .position(|record| Self::matches(record, semester, attempt));
+
+                match position {
+                    Some(position) => {
+                        let record = &mut self.records[position];
+                        if record.grade != grade {
+                            updates.push(GradeUpdate {
+                                semester,
+                                name: item.name.clone(),
+                                grade: grade.clone(),
+                                previous: Some(record.grade.clone()),
+                            });
+                            record.changes.push(GradeChange {
+                                at: at.clone(),
+                                from: record.grade.clone(),
+                                to: grade.clone(),
+                            });
+                            record.grade = grade;
+                        }
+                    }
+                    None => {
+                        if !baseline {
+                            updates.push(GradeUpdate {
+                                semester,
+                                name: item.name.clone(),
+                                grade: grade.clone(),
+                                previous: None,
+                            });
+                        }
+                        self.records.push(GradeRecord {
+                            semester,
+                            name: item.name.clone(),
+                            r#type: item.r#type.to_string().to_string(),
+                            attempt: attempt.number - 1,
+                            grade,
+                            first_seen: at.clone(),
+                            baseline,
+                            changes: vec![],
+                        });
+                    }
+                }
+            }
+        }
+
+        updates
+    }
+}
+
+pub fn get_history() -> Result<GradeHistory, ConfyError> {
+    config::load_data(HISTORY_FILE)
+}
+
+pub fn store_history(history: GradeHistory) -> Result<(), ConfyError> {
+    config::store_data(HISTORY_FILE, history)
+}
+
+/// Diffs a freshly fetched table against the stored one and saves the result.
+/// Nothing is saved if the stored history couldn't be read.
+fn history_update(semesters: &[Vec<GradeItem>; 8]) -> Result<Vec<GradeUpdate>, ConfyError> {
+    let mut history = get_history()?;
+    let first_fetch = history.records.is_empty();
+    let updates = history.update(semesters, timezone::now().naive_local());
+
+    if first_fetch || !updates.is_empty() {
+        store_history(history)?;
+    }
+
+    Ok(updates)
+}
+
+/// Records a fetched table and shows what changed. Runs on the UI thread,
+/// so fetches finishing at the same time don't overwrite each other's records.
+pub fn history_record(s: &mut Cursive, semesters: &[Vec<GradeItem>; 8]) {
+    match history_update(semesters) {
+        Ok(updates) if !updates.is_empty() => history_notify(s, &updates),
+        Ok(_) => {}
+        Err(err) => crate::data_error_dialog(s, HISTORY_FILE, err),
+    }
+}
+
+fn grade_str(grade: &str) -> String {
+    t!("grades_grade.".to_owned() + grade).to_string()
+}
+
+fn history_notify(s: &mut Cursive, updates: &[GradeUpdate]) {
+    let mut text = StyledString::new();
+
+    for update in updates {
+        text.append_styled(format!("■ {}\n", update.name), Effect::Bold);
+        text.append_plain(match &update.previous {
+            Some(previous) => format!(
+                "  {}: {} → {}\n",
+                t!("debts.semester", n = update.semester),
+                grade_str(previous),
+                grade_str(&update.grade)
+            ),
+            None => format!(
+                "  {}: {}\n",
+                t!("debts.semester", n = update.semester),
+                grade_str(&update.grade)
+            ),
+        });
+    }
+
+    s.add_layer(
+        Dialog::around(TextView::new(text).scrollable())
+            .title(t!("history.notification", count = updates.len()))
+            .dismiss_button(t!("actions.close")),
+    );
+}
+
+/// When each grade first appeared and whether it changed afterwards, newest first
+pub fn history_view(s: &mut Cursive) {
+    let history = match get_history() {
+        Ok(history) => history,
+        Err(err) => return crate::data_error_dialog(s, HISTORY_FILE, err),
+    };
+    let now = timezone::now().naive_local();
+    let mut records: Vec<&GradeRecord> = history.records.iter().collect();
+    records.sort_by(|a, b| b.first_seen.cmp(&a.first_seen));
+
+    let mut text = StyledString::new();
+    if records.is_empty() {
+        text.append_plain(t!("history.empty"));
+    }
+
+    for record in records {
+        let seen = if record.baseline {
+            t!("history.tracked_since", at = &record.first_seen).to_string()
+        } else {
+            record.first_seen.clone()
+        };
+
+        text.append_styled(format!("{} ", seen), ColorStyle::tertiary());
+        if record.is_new(now) {
+            text.append_styled(
+                format!("{} ", t!("history.new")),
+                ColorStyle::front(BaseColor::Green),
+            );
+        }
+        text.append_plain(format!(
+            "{} — {} ({})\n",
+            record.name,
+            grade_str(&record.grade),
+            t!("debts.semester", n = record.semester)
+        ));
+
+        for change in &record.changes {
+            text.append_styled(
+                format!(
+                    "  {}\n",
+                    t!(
+                        "history.changed",
+                        at = &change.at,
+                        from = grade_str(&change.from),
+                        to = grade_str(&change.to)
+                    )
+                ),
+                ColorStyle::front(BaseColor::Yellow),
+            );
+        }
+    }
+
+    s.add_layer(
+        Dialog::around(TextView::new(text).scrollable())
+            .title(t!("history.title"))
+            .dismiss_button(t!("actions.close")),
+    );
+}
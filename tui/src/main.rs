@@ -1,7 +1,20 @@
+mod attendance;
+mod charts;
 mod config;
+mod credentials;
+mod dashboard;
+mod diploma;
+mod events;
 mod grades;
+mod history;
+mod notes;
 mod schedules;
+mod scholarship;
+mod search;
 mod setup;
+mod state;
+mod stats;
+mod timetable;
 
 use confy::ConfyError;
 use cursive::{self};
@@ -14,18 +27,22 @@ use cursive::{
     align::Align,
     event::Event,
     menu::Tree,
-    view::Resizable,
+    view::{Nameable, Resizable},
     views::{Dialog, LinearLayout, TextView},
 };
 
 use config::LoungeConfig;
 
-use crate::{grades::grades_view, schedules::schedules_view};
+use crate::{dashboard::dashboard_start, schedules::schedules_view};
 
 pub fn main_screen(s: &mut Cursive) {
     for event in [
         Event::Key(cursive::event::Key::F2),
         Event::Key(cursive::event::Key::F1),
+        Event::Key(cursive::event::Key::F3),
+        Event::Key(cursive::event::Key::F4),
+        Event::Key(cursive::event::Key::F5),
+        Event::Key(cursive::event::Key::F6),
     ] {
         s.clear_global_callbacks(event);
     }
@@ -35,11 +52,14 @@ pub fn main_screen(s: &mut Cursive) {
         s.set_autohide_menu(true);
         s.add_layer(schedules_view);
     });
-    s.add_global_callback(Event::Key(cursive::event::Key::F2), |s| {
-        let grades_view = grades_view(s);
-        s.set_autohide_menu(true);
-        s.add_layer(grades_view);
-    });
+    s.add_global_callback(Event::Key(cursive::event::Key::F2), grades::grades_open);
+    s.add_global_callback(Event::Key(cursive::event::Key::F3), notes::homework_view);
+    s.add_global_callback(Event::Key(cursive::event::Key::F4), events::events_view);
+    s.add_global_callback(Event::Key(cursive::event::Key::F5), stats::stats_view);
+    s.add_global_callback(
+        Event::Key(cursive::event::Key::F6),
+        attendance::attendance_view,
+    );
 
     s.screen_mut().add_transparent_layer(
         LinearLayout::vertical()
@@ -48,6 +68,11 @@ pub fn main_screen(s: &mut Cursive) {
                     .align(Align::center())
                     .no_wrap(),
             )
+            .child(
+                TextView::new(t!("dashboard.loading"))
+                    .align(Align::center())
+                    .with_name("dashboard"),
+            )
             .child(TextView::new(t!("about_description_1")).align(Align::center()))
             .child(TextView::new(t!("about_description_2")).align(Align::center()))
             .child(TextView::new(t!("about_developer")).align(Align::center()))
@@ -64,12 +89,19 @@ pub fn main_screen(s: &mut Cursive) {
             s.set_autohide_menu(true);
             s.add_layer(schedules_view);
         });
+    // the badge is found by its label later, so it has to match the known debts count
+    let grades_label = grades::grades_menu_label(state::get_state(s).debts);
+    s.menubar().add_leaf(grades_label, grades::grades_open);
     s.menubar()
-        .add_leaf(format!("[F2] {}", t!("sections.grades")), |s| {
-            let grades_view = grades_view(s);
-            s.set_autohide_menu(true);
-            s.add_layer(grades_view);
-        });
+        .add_leaf(format!("[F3] {}", t!("sections.homework")), notes::homework_view);
+    s.menubar()
+        .add_leaf(format!("[F4] {}", t!("sections.events")), events::events_view);
+    s.menubar()
+        .add_leaf(format!("[F5] {}", t!("sections.stats")), stats::stats_view);
+    s.menubar().add_leaf(
+        format!("[F6] {}", t!("sections.attendance")),
+        attendance::attendance_view,
+    );
     s.menubar().add_delimiter();
 
     let settings_tree = Tree::new()
@@ -82,12 +114,25 @@ pub fn main_screen(s: &mut Cursive) {
             setup::grades_settings(s)
         })
         .delimiter()
+        .leaf(t!("actions.specify_filters"), |s| {
+            setup::lesson_filters_settings(s)
+        })
+        .leaf(t!("actions.specify_overlays"), |s| {
+            setup::overlay_calendars_settings(s)
+        })
+        .leaf(t!("actions.specify_absence_limit"), |s| {
+            setup::absence_limit_settings(s)
+        })
+        .delimiter()
         .leaf(t!("actions.specify_theme"), |s| {
             setup::select_theme(s);
         });
 
     s.menubar()
         .add_subtree(format!("[▼] {}", t!("sections.settings")), settings_tree);
+
+    dashboard_start(s);
+    grades::grades_badge_start(s);
 }
 
 pub fn welcome(s: &mut Cursive) {
@@ -121,6 +166,17 @@ pub fn error_dialog(s: &mut Cursive, error: ConfyError) {
     );
 }
 
+/// A data file (notes, events, ...) failed to load. It is left untouched so nothing gets lost.
+pub fn data_error_dialog(s: &mut Cursive, name: &str, error: ConfyError) {
+    let path = config::get_data_path(name)
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
+
+    s.add_layer(
+        Dialog::info(t!("errors.data", e = &error.to_string(), path = path)).title(t!("error")),
+    );
+}
+
 fn main() {
     rust_i18n::set_locale("ru");
     let cfg: Result<config::LoungeConfig, ConfyError> = config::get_config();
@@ -0,0 +1,293 @@
+use chrono::NaiveDate;
+use confy::ConfyError;
+use cursive::{
+    Cursive,
+    theme::{BaseColor, ColorStyle},
+    utils::markup::StyledString,
+    view::{Nameable, Resizable, Scrollable},
+    views::{Checkbox, Dialog, EditView, LinearLayout, SelectView, TextArea, TextView},
+};
+use lounge_parser::{schedules::LessonKey, timezone};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::config;
+
+use rust_i18n::t;
+
+rust_i18n::i18n!();
+
+const NOTES_FILE: &str = "notes";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LessonNote {
+    pub key: LessonKey,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub homework: String,
+    /// %d.%m.%Y, the lesson itself when not set
+    #[serde(default)]
+    pub deadline: Option<String>,
+    #[serde(default)]
+    pub done: bool,
+}
+
+impl LessonNote {
+    pub fn new(key: LessonKey) -> Self {
+        Self {
+            key,
+            text: String::new(),
+            homework: String::new(),
+            deadline: None,
+            done: false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.homework.trim().is_empty()
+    }
+
+    pub fn has_homework(&self) -> bool {
+        !self.homework.trim().is_empty()
+    }
+
+    pub fn due_date(&self) -> Option<NaiveDate> {
+        match &self.deadline {
+            Some(deadline) => NaiveDate::parse_from_str(deadline, "%d.%m.%Y").ok(),
+            None => self.key.date(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct LessonNotes {
+    #[serde(default)]
+    pub notes: Vec<LessonNote>,
+}
+
+impl LessonNotes {
+    pub fn get(&self, key: &LessonKey) -> Option<&LessonNote> {
+        self.notes.iter().find(|note| &note.key == key)
+    }
+
+    /// Replaces the note for the same lesson, empty notes are dropped
+    pub fn set(&mut self, note: LessonNote) {
+        self.remove(&note.key);
+        if !note.is_empty() {
+            self.notes.push(note);
+        }
+    }
+
+    pub fn remove(&mut self, key: &LessonKey) {
+        self.notes.retain(|note| &note.key != key);
+    }
+
+    /// Unfinished homework due today or later, soonest first
+    pub fn homework_due(&self, today: NaiveDate) -> Vec<&LessonNote> {
+        let mut result: Vec<&LessonNote> = self
+            .notes
+            .iter()
+            .filter(|note| note.has_homework() && !note.done)
+            .filter(|note| note.due_date().is_none_or(|date| date >= today))
+            .collect();
+
+        result.sort_by_key(|note| note.due_date());
+        result
+    }
+}
+
+pub fn get_notes() -> Result<LessonNotes, ConfyError> {
+    config::load_data(NOTES_FILE)
+}
+
+/// Loads the notes, or reports why they couldn't be read
+fn notes_or_error(s: &mut Cursive) -> Option<LessonNotes> {
+    get_notes()
+        .map_err(|err| crate::data_error_dialog(s, NOTES_FILE, err))
+        .ok()
+}
+
+pub fn store_notes(notes: LessonNotes) -> Result<(), ConfyError> {
+    config::store_data(NOTES_FILE, notes)
+}
+
+fn note_title(key: &LessonKey) -> String {
+    let date = key
+        .date()
+        .map(|date| date.format("%d.%m.%Y").to_string())
+        .unwrap_or(key.date.clone());
+
+    format!("{}, {}", date, key.subject)
+}
+
+fn note_save(s: &mut Cursive, key: &LessonKey, on_close: fn(&mut Cursive)) {
+    let text = s
+        .call_on_name("note-text", |view: &mut TextArea| {
+            view.get_content().to_string()
+        })
+        .unwrap();
+    let homework = s
+        .call_on_name("note-homework", |view: &mut TextArea| {
+            view.get_content().to_string()
+        })
+        .unwrap();
+    let deadline = s
+        .call_on_name("note-deadline", |view: &mut EditView| {
+            view.get_content().trim().to_string()
+        })
+        .unwrap();
+    let done = s
+        .call_on_name("note-done", |view: &mut Checkbox| view.is_checked())
+        .unwrap();
+
+    if !deadline.is_empty() && NaiveDate::parse_from_str(&deadline, "%d.%m.%Y").is_err() {
+        s.add_layer(Dialog::info(t!("notes.invalid_deadline")));
+        return;
+    }
+
+    let Some(mut notes) = notes_or_error(s) else {
+        return;
+    };
+    notes.set(LessonNote {
+        key: key.clone(),
+        text,
+        homework,
+        deadline: if deadline.is_empty() {
+            None
+        } else {
+            Some(deadline)
+        },
+        done,
+    });
+
+    note_store_and_close(s, notes, on_close);
+}
+
+fn note_store_and_close(s: &mut Cursive, notes: LessonNotes, on_close: fn(&mut Cursive)) {
+    match store_notes(notes) {
+        Ok(_) => {
+            s.pop_layer();
+            on_close(s);
+        }
+        Err(err) => {
+            s.add_layer(Dialog::info(t!("errors.notes", e = err.to_string())));
+        }
+    }
+}
+
+/// Editor for the note of a single lesson. `on_close` runs after saving, e.g. to redraw markers.
+pub fn note_editor(s: &mut Cursive, key: LessonKey, on_close: fn(&mut Cursive)) {
+    let Some(notes) = notes_or_error(s) else {
+        return;
+    };
+    let note = notes
+        .get(&key)
+        .cloned()
+        .unwrap_or(LessonNote::new(key.clone()));
+
+    let save_key = key.clone();
+    let delete_key = key.clone();
+
+    let dialog = Dialog::around(
+        LinearLayout::vertical()
+            .child(TextView::new(t!("notes.text")))
+            .child(
+                TextArea::new()
+                    .content(&note.text)
+                    .with_name("note-text")
+                    .min_height(3),
+            )
+            .child(TextView::new(format!("\n{}", t!("notes.homework"))))
+            .child(
+                TextArea::new()
+                    .content(&note.homework)
+                    .with_name("note-homework")
+                    .min_height(3),
+            )
+            .child(TextView::new(format!("\n{}", t!("notes.deadline"))))
+            .child(
+                EditView::new()
+                    .content(note.deadline.clone().unwrap_or_default())
+                    .with_name("note-deadline"),
+            )
+            .child(
+                LinearLayout::horizontal()
+                    .child(
+                        Checkbox::new()
+                            .with_checked(note.done)
+                            .with_name("note-done"),
+                    )
+                    .child(TextView::new(format!(" {}", t!("notes.done")))),
+            )
+            .fixed_width(40),
+    )
+    .title(note_title(&key))
+    .button(t!("actions.apply"), move |s| {
+        note_save(s, &save_key, on_close)
+    })
+    .button(t!("notes.delete"), move |s| {
+        let Some(mut notes) = notes_or_error(s) else {
+            return;
+        };
+        notes.remove(&delete_key);
+        note_store_and_close(s, notes, on_close);
+    })
+    .dismiss_button(t!("actions.cancel"));
+
+    s.add_layer(dialog);
+}
+
+fn homework_reload(s: &mut Cursive) {
+    s.pop_layer();
+    homework_view(s);
+}
+
+/// Upcoming homework across all weeks, straight from the notes file
+pub fn homework_view(s: &mut Cursive) {
+    let Some(notes) = notes_or_error(s) else {
+        return;
+    };
+    let today = timezone::today();
+    let due = notes.homework_due(today);
+
+    let content: Box<dyn cursive::View> = if due.is_empty() {
+        Box::new(TextView::new(t!("notes.no_homework")))
+    } else {
+        let mut list = SelectView::<LessonKey>::new();
+
+        for note in due {
+            let mut label = StyledString::new();
+            let due_date = note.due_date();
+            let color = match due_date {
+                Some(date) if date == today => ColorStyle::front(BaseColor::Red),
+                Some(date) if (date - today).num_days() < 3 => ColorStyle::front(BaseColor::Yellow),
+                _ => ColorStyle::primary(),
+            };
+
+            label.append_styled(
+                format!(
+                    "{} ",
+                    due_date
+                        .map(|date| date.format("%d.%m").to_string())
+                        .unwrap_or("??.??".to_string())
+                ),
+                color,
+            );
+            label.append_plain(format!(
+                "{}: {}",
+                note.key.subject,
+                note.homework.trim().replace('\n', " ")
+            ));
+            list.add_item(label, note.key.clone());
+        }
+
+        list.set_on_submit(|s, key: &LessonKey| note_editor(s, key.clone(), homework_reload));
+        Box::new(list.scrollable())
+    };
+
+    s.add_layer(
+        Dialog::around(LinearLayout::vertical().child(content).fixed_width(50))
+            .title(t!("notes.homework_due"))
+            .dismiss_button(t!("actions.close")),
+    );
+}
@@ -1,14 +1,19 @@
 use std::env;
 
-use crate::{config, setup};
-use chrono::{Days, TimeZone, Utc};
+use crate::{
+    attendance::{self, Attendance},
+    config, events,
+    notes::{self, LessonNotes},
+    search, setup, state, stats, timetable,
+};
+use chrono::{Days, Months, NaiveDate, NaiveTime, TimeDelta};
 use cursive::{
     Cursive,
     align::Align,
-    theme::{Effects, Style},
+    theme::{Effect, Effects, Style},
     utils::markup::StyledString,
     view::{Margins, Scrollable},
-    views::{Button, Dialog, LinearLayout, NamedView, PaddedView, TextView},
+    views::{Button, Dialog, LinearLayout, NamedView, OnEventView, PaddedView, TextView},
 };
 use cursive::{
     theme::{BaseColor, ColorStyle, PaletteStyle},
@@ -16,16 +21,89 @@ use cursive::{
 };
 use cursive_async_view::AsyncView;
 use lounge_parser::{
+    events::{PersonalEvent, day_conflicts, merge_events},
+    filters::LessonFilters,
+    gaps::{day_gaps, day_summary},
     get_schedules,
-    schedules::{DayItem, LessonItem, LessonUrl, additional::LessonType},
+    grades::{GradeItem, GradeResult},
+    ical,
+    matching::find_grade,
+    schedules::{DayItem, LessonItem, LessonKey, LessonUrl, additional::LessonType},
+    timezone,
 };
+use serde_derive::{Deserialize, Serialize};
 use tokio::runtime::Runtime;
 
 use rust_i18n::t;
 
 rust_i18n::i18n!();
 
-fn schedules_additional_type_to_text(text: &str) -> String {
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub enum SchedulesSpan {
+    Day,
+    #[default]
+    Week,
+    TwoWeeks,
+    Month,
+}
+
+impl SchedulesSpan {
+    pub fn date_to(&self, date_from: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => date_from,
+            Self::Week => date_from + Days::new(6),
+            Self::TwoWeeks => date_from + Days::new(13),
+            Self::Month => date_from + Months::new(1) - Days::new(1),
+        }
+    }
+
+    pub fn days(&self, date_from: NaiveDate) -> usize {
+        (self.date_to(date_from) - date_from).num_days() as usize + 1
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Day => Self::Week,
+            Self::Week => Self::TwoWeeks,
+            Self::TwoWeeks => Self::Month,
+            Self::Month => Self::Day,
+        }
+    }
+
+    pub fn to_text(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::TwoWeeks => "two_weeks",
+            Self::Month => "month",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum SchedulesLayout {
+    #[default]
+    List,
+    Grid,
+}
+
+impl SchedulesLayout {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::List => Self::Grid,
+            Self::Grid => Self::List,
+        }
+    }
+
+    pub fn to_text(self) -> &'static str {
+        match self {
+            Self::List => "list",
+            Self::Grid => "grid",
+        }
+    }
+}
+
+pub fn schedules_additional_type_to_text(text: &str) -> String {
     return t!("schedules_type.".to_owned() + text).to_string();
 }
 
@@ -55,8 +133,8 @@ fn schedules_links_view(urls: Vec<LessonUrl>) -> LinearLayout {
     url_button_bar
 }
 
-fn schedules_type_difficulty_view(r#type: &LessonType) -> LinearLayout {
-    let color = match r#type {
+pub fn schedules_type_color(r#type: &LessonType) -> ColorStyle {
+    match r#type {
         LessonType::Lecture => ColorStyle::front(BaseColor::Green),
         LessonType::Practice => ColorStyle::front(BaseColor::Green),
         LessonType::Meeting => ColorStyle::front(BaseColor::Yellow),
@@ -64,15 +142,21 @@ fn schedules_type_difficulty_view(r#type: &LessonType) -> LinearLayout {
         LessonType::SubjectReportWithGrade => ColorStyle::front(BaseColor::Yellow),
         LessonType::Consultation => ColorStyle::front(BaseColor::Yellow),
         LessonType::Exam => ColorStyle::front(BaseColor::Red),
+        LessonType::Personal => ColorStyle::front(BaseColor::Magenta),
+        LessonType::External => ColorStyle::front(BaseColor::Cyan),
         _ => ColorStyle::front(BaseColor::White),
-    };
+    }
+}
+
+fn schedules_type_difficulty_view(r#type: &LessonType) -> LinearLayout {
+    let color = schedules_type_color(r#type);
 
     LinearLayout::vertical()
         .child(TextView::new("║ ").style(color))
         .child(TextView::new("║ ").style(color))
 }
 
-fn schedules_lesson_place_str(lesson: &LessonItem) -> String {
+pub fn schedules_lesson_place_str(lesson: &LessonItem) -> String {
     let lesson_place = if !lesson.additional.online {
         lesson
             .additional
@@ -97,7 +181,7 @@ fn lesson_times_view(lesson: &LessonItem) -> LinearLayout {
     lesson_times
 }
 
-fn lesson_type_place_view(lesson_type: String, lesson_place: String) -> LinearLayout {
+pub fn lesson_type_place_view(lesson_type: String, lesson_place: String) -> LinearLayout {
     let lesson_type_place = LinearLayout::horizontal()
         .child(TextView::new(lesson_type).style(ColorStyle::new(BaseColor::White, BaseColor::Red)))
         .child(TextView::new(" "))
@@ -107,23 +191,102 @@ fn lesson_type_place_view(lesson_type: String, lesson_place: String) -> LinearLa
     lesson_type_place
 }
 
-fn schedules_list_view(result: Result<Vec<DayItem>, String>) -> LinearLayout {
+// 100 -> "1h40"
+fn schedules_duration_str(minutes: i64) -> String {
+    t!(
+        "gaps.duration",
+        h = minutes / 60,
+        m = format!("{:02}", minutes % 60)
+    )
+    .to_string()
+}
+
+fn schedules_note_button(key: LessonKey, has_note: bool) -> Button {
+    let label = if has_note {
+        t!("notes.edit")
+    } else {
+        t!("notes.add")
+    };
+
+    Button::new(label, move |s| {
+        notes::note_editor(s, key.clone(), schedules_reload)
+    })
+}
+
+/// Whether an upcoming exam or credit already has a grade
+fn schedules_grade_str(text: &mut StyledString, grades: &[Vec<GradeItem>; 8], lesson: &LessonItem) {
+    if !lesson.additional.r#type.is_assessment() {
+        return;
+    }
+
+    match find_grade(grades, lesson) {
+        Some((_, item)) if !matches!(item.grade, GradeResult::Unknown) => text.append_styled(
+            format!(
+                "\n✓ {}",
+                t!(
+                    "matching.graded",
+                    grade = t!("grades_grade.".to_owned() + item.grade.to_string())
+                )
+            ),
+            ColorStyle::front(BaseColor::Green),
+        ),
+        _ => text.append_styled(
+            format!("\n… {}", t!("matching.not_graded")),
+            ColorStyle::tertiary(),
+        ),
+    }
+}
+
+fn schedules_list_view(
+    result: Result<Vec<DayItem>, String>,
+    filters: &LessonFilters,
+    notes: &LessonNotes,
+    attendance: &Attendance,
+    grades: Option<&[Vec<GradeItem>; 8]>,
+    date_from: NaiveDate,
+) -> LinearLayout {
     let mut schedules_list = LinearLayout::vertical();
+    let now = timezone::now().naive_local();
 
     match result {
         Ok(schedules) => {
             for day in schedules {
                 let mut lesson_list_view = LinearLayout::vertical();
+                let date = day.date(date_from);
+                let conflicts = day_conflicts(&day);
+                let gaps = day_gaps(&day);
+                let summary = day_summary(&day);
 
-                for lesson in day.lessons {
+                for (index, (lesson, is_conflict)) in
+                    day.lessons.into_iter().zip(conflicts).enumerate()
+                {
+                    let key = date.map(|date| lesson.key(date));
+                    let note = key.as_ref().and_then(|key| notes.get(key));
+                    // only reachable when hidden lessons are temporarily shown
+                    let is_hidden = filters.is_hidden(&lesson);
                     let lesson_type =
                         schedules_additional_type_to_text(&lesson.additional.r#type.to_text());
                     let lesson_place = schedules_lesson_place_str(&lesson);
                     let lesson_times = lesson_times_view(&lesson);
+                    // attendance can only be marked once the lesson is over
+                    let is_past = date
+                        .zip(NaiveTime::parse_from_str(lesson.time_end.trim(), "%H:%M").ok())
+                        .is_some_and(|(date, time_end)| date.and_time(time_end) <= now);
+                    let mut grade_text = StyledString::new();
+                    if let Some(grades) = grades.filter(|_| !is_past) {
+                        schedules_grade_str(&mut grade_text, grades, &lesson);
+                    }
 
                     // type & place / text / urls / etc
                     let mut lesson_text = StyledString::new();
-                    lesson_text.append_plain(&lesson.text);
+                    if note.is_some() {
+                        lesson_text.append_styled("✎ ", ColorStyle::front(BaseColor::Yellow));
+                    }
+                    if lesson.additional.r#type.is_official() {
+                        lesson_text.append_plain(&lesson.text);
+                    } else {
+                        lesson_text.append_styled(&lesson.text, Effect::Italic);
+                    }
                     match lesson.additional.teacher_name {
                         Some(teacher) => {
                             lesson_text.append_plain(" ");
@@ -137,11 +300,41 @@ fn schedules_list_view(result: Result<Vec<DayItem>, String>) -> LinearLayout {
                         }
                         None => (),
                     }
+                    if is_hidden {
+                        lesson_text.append_styled(
+                            format!(" [{}]", t!("filters.hidden_marker")),
+                            Effect::Italic,
+                        );
+                    }
+                    if is_conflict {
+                        lesson_text.append_styled(
+                            format!("\n⚠ {}", t!("events.conflict")),
+                            ColorStyle::front(BaseColor::Red),
+                        );
+                    }
+                    lesson_text.append(grade_text);
+                    if let Some(note) = note.filter(|note| note.has_homework()) {
+                        lesson_text.append_styled(
+                            format!("\n{}: {}", t!("notes.homework_short"), note.homework.trim()),
+                            ColorStyle::front(BaseColor::Yellow),
+                        );
+                    }
+
+                    let mut lesson_links = LinearLayout::horizontal();
+                    if let Some(key) = key {
+                        if is_past && lesson.additional.r#type.is_official() {
+                            let mark = attendance.get(&key);
+                            lesson_links
+                                .add_child(attendance::attendance_button(key.clone(), mark));
+                        }
+                        lesson_links.add_child(schedules_note_button(key, note.is_some()));
+                    }
+                    lesson_links.add_child(schedules_links_view(lesson.urls));
 
                     let lesson_body: LinearLayout = LinearLayout::vertical()
                         .child(lesson_type_place_view(lesson_type, lesson_place))
                         .child(TextView::new(lesson_text).full_width().max_width(40))
-                        .child(schedules_links_view(lesson.urls).child(TextView::new(" ")));
+                        .child(lesson_links.child(TextView::new(" ")));
 
                     // difficulty | body | times
                     let lesson_view = LinearLayout::horizontal()
@@ -150,12 +343,43 @@ fn schedules_list_view(result: Result<Vec<DayItem>, String>) -> LinearLayout {
                         .child(lesson_times);
 
                     lesson_list_view.add_child(lesson_view);
+
+                    for gap in gaps.iter().filter(|gap| gap.after == index) {
+                        lesson_list_view.add_child(
+                            TextView::new(format!(
+                                "  ┄ {} ({}–{})\n",
+                                t!(
+                                    "gaps.window",
+                                    duration = schedules_duration_str(gap.minutes())
+                                ),
+                                gap.from.format("%H:%M"),
+                                gap.to.format("%H:%M")
+                            ))
+                            .style(PaletteStyle::Tertiary),
+                        );
+                    }
                 }
 
-                schedules_list.add_child(TextView::new(format!(
-                    "{}, {}.{}\n\n",
+                let mut day_header = StyledString::plain(format!(
+                    "{}, {}.{}\n",
                     &day.week_day, &day.day, &day.month
-                )));
+                ));
+                if let (Some(first_start), Some(last_end)) = (summary.first_start, summary.last_end)
+                {
+                    day_header.append_styled(
+                        t!(
+                            "gaps.summary",
+                            pairs = summary.pairs,
+                            from = first_start.format("%H:%M"),
+                            to = last_end.format("%H:%M"),
+                            hours = schedules_duration_str(summary.contact_minutes)
+                        ),
+                        ColorStyle::tertiary(),
+                    );
+                    day_header.append_plain("\n");
+                }
+                day_header.append_plain("\n");
+                schedules_list.add_child(TextView::new(day_header));
 
                 schedules_list.add_child(PaddedView::new(Margins::tb(0, 1), lesson_list_view));
             }
@@ -168,42 +392,198 @@ fn schedules_list_view(result: Result<Vec<DayItem>, String>) -> LinearLayout {
     schedules_list
 }
 
-pub fn schedules_view(siv: &mut Cursive) -> NamedView<Dialog> {
+pub fn schedules_reload(s: &mut Cursive) {
+    s.pop_layer();
+    let schedules_view = schedules_view(s);
+    s.add_layer(schedules_view);
+}
+
+fn schedules_shift(s: &mut Cursive, days: i64) {
+    let state = state::get_state(s);
+    state.schedules_date += TimeDelta::days(days);
+    schedules_reload(s);
+}
+
+fn schedules_today(s: &mut Cursive) {
+    state::get_state(s).schedules_date = timezone::today();
+    schedules_reload(s);
+}
+
+fn schedules_next_span(s: &mut Cursive) {
+    let mut cfg = config::get_config().unwrap();
+    cfg.schedules_span = cfg.schedules_span.next();
+    config::store_config(cfg).unwrap();
+    schedules_reload(s);
+}
+
+fn schedules_toggle_hidden(s: &mut Cursive) {
+    let state = state::get_state(s);
+    state.show_hidden = !state.show_hidden;
+    schedules_reload(s);
+}
+
+fn schedules_next_layout(s: &mut Cursive) {
+    let mut cfg = config::get_config().unwrap();
+    cfg.schedules_layout = cfg.schedules_layout.next();
+    config::store_config(cfg).unwrap();
+    schedules_reload(s);
+}
+
+/// Fetches the lessons of the period merged with personal events, the way the schedules view shows them
+fn schedules_fetch(
+    rt: &Runtime,
+    date_from: NaiveDate,
+    date_to: NaiveDate,
+    group_id: &str,
+    personal_events: &[PersonalEvent],
+) -> Result<Vec<DayItem>, String> {
+    let mut days = rt.block_on(get_schedules(
+        &date_from.format("%d.%m.%Y").to_string(),
+        &date_to.format("%d.%m.%Y").to_string(),
+        group_id,
+    ))?;
+    merge_events(&mut days, personal_events, date_from, date_to);
+
+    Ok(days)
+}
+
+fn schedules_export(s: &mut Cursive) {
+    let cfg = config::get_config().unwrap();
+    let date_from = state::get_state(s).schedules_date;
+    let date_to = cfg.schedules_span.date_to(date_from);
+    let personal_events = events::all_events(&cfg);
+
+    let async_view = AsyncView::new_with_bg_creator(
+        s,
+        move || {
+            let rt = Runtime::new().unwrap();
+            let result = schedules_fetch(&rt, date_from, date_to, &cfg.group_id, &personal_events)
+                .and_then(|mut days| {
+                    cfg.filters.apply(&mut days);
+                    let body = ical::schedules_to_ical(
+                        &days,
+                        date_from,
+                        &t!("sections.schedules"),
+                        &chrono::Utc::now(),
+                    );
+                    config::write_file(
+                        &format!(
+                            "{}-{}.ics",
+                            date_from.format("%Y%m%d"),
+                            date_to.format("%Y%m%d")
+                        ),
+                        &body,
+                    )
+                });
+
+            Ok(result)
+        },
+        |result| match result {
+            Ok(path) => TextView::new(t!("export.saved", path = path.display().to_string())),
+            Err(err) => TextView::new(t!("errors.some", e = err)),
+        },
+    );
+
+    s.add_layer(
+        Dialog::around(async_view.with_width(40))
+            .title(t!("export.title"))
+            .dismiss_button(t!("actions.close")),
+    );
+}
+
+pub fn schedules_view(siv: &mut Cursive) -> OnEventView<NamedView<Dialog>> {
     let cfg = config::get_config().unwrap();
-    let date = Utc.timestamp(cfg.selected_date, 0);
-    let date_from_formatted = date.format("%d.%m.%Y").to_string();
-    let date_to = date.checked_add_days(Days::new(7)).unwrap();
-    let date_to = date_to.format("%d.%m.%Y").to_string();
+    let (date, show_hidden) = {
+        let state = state::get_state(siv);
+        (state.schedules_date, state.show_hidden)
+    };
+    let date_to = cfg.schedules_span.date_to(date);
+    let title = format!(
+        "{} {} — {}",
+        t!("sections.schedules"),
+        date.format("%d.%m"),
+        date_to.format("%d.%m")
+    );
+    let span_label = t!(
+        "schedules.span",
+        span = t!("schedules_span.".to_owned() + cfg.schedules_span.to_text())
+    );
+    let layout_label = t!(
+        "schedules.layout",
+        layout = t!("schedules_layout.".to_owned() + cfg.schedules_layout.to_text())
+    );
+    let layout = cfg.schedules_layout;
+    let (cell_width, width) = match layout {
+        SchedulesLayout::List => (0, 40),
+        SchedulesLayout::Grid => {
+            timetable::timetable_width(siv.screen_size().x, cfg.schedules_span.days(date))
+        }
+    };
+    let filters = cfg.filters.clone();
+    // Markers only, a broken notes or attendance file is reported once it is opened
+    let notes = notes::get_notes().unwrap_or_default();
+    let attendance = attendance::get_attendance().unwrap_or_default();
+    let grades = state::get_state(siv).grades.clone();
+    let personal_events = events::all_events(&cfg);
 
     let async_view = AsyncView::new_with_bg_creator(
         siv,
         move || {
             let rt = Runtime::new().unwrap();
-            let schedules_result =
-                rt.block_on(get_schedules(&date_from_formatted, &date_to, &cfg.group_id));
+            let mut schedules_result =
+                schedules_fetch(&rt, date, date_to, &cfg.group_id, &personal_events);
+
+            if !show_hidden && let Ok(days) = &mut schedules_result {
+                cfg.filters.apply(days);
+            }
 
             // enough blocking, let's show the content
             Ok(schedules_result)
         },
-        schedules_list_view,
+        move |result| match layout {
+            SchedulesLayout::List => {
+                schedules_list_view(result, &filters, &notes, &attendance, grades.as_deref(), date)
+            }
+            SchedulesLayout::Grid => timetable::timetable_view(result, cell_width, &notes, date),
+        },
     ); // create a text view from the string
 
-    return Dialog::around(async_view.with_width(40).scrollable())
-        .title(t!("sections.schedules"))
-        .button(t!("schedules.change_date"), |s| {
-            setup::select_date(s);
-        })
-        .button(t!("schedules.today"), |s| {
-            let mut cfg = config::get_config().unwrap();
-            cfg.selected_date = Utc::now().timestamp();
-            config::store_config(cfg).unwrap();
-            let schedules_view = schedules_view(s);
-            s.pop_layer();
-            s.add_layer(schedules_view);
-        })
-        .button(t!("actions.close"), |s| {
-            s.set_autohide_menu(false);
-            s.pop_layer();
-        })
-        .with_name("schedules");
+    let dialog = Dialog::around(
+        LinearLayout::vertical()
+            .child(
+                TextView::new(t!("schedules.hotkeys"))
+                    .style(PaletteStyle::Tertiary)
+                    .max_width(40),
+            )
+            .child(async_view.with_width(width).scrollable().scroll_x(true)),
+    )
+    .title(title)
+    .button(t!("schedules.change_date"), |s| {
+        setup::select_date(s);
+    })
+    .button(t!("schedules.today"), schedules_today)
+    .button(span_label, schedules_next_span)
+    .button(layout_label, schedules_next_layout)
+    .button(t!("search.title"), search::search_prompt)
+    .button(t!("actions.close"), |s| {
+        s.set_autohide_menu(false);
+        s.pop_layer();
+    })
+    .with_name("schedules");
+
+    OnEventView::new(dialog)
+        .on_event(',', |s| schedules_shift(s, -1))
+        .on_event('.', |s| schedules_shift(s, 1))
+        .on_event('<', |s| schedules_shift(s, -7))
+        .on_event('>', |s| schedules_shift(s, 7))
+        .on_event('t', schedules_today)
+        .on_event('r', schedules_next_span)
+        .on_event('g', schedules_next_layout)
+        .on_event('/', search::search_prompt)
+        .on_event('h', schedules_toggle_hidden)
+        .on_event('w', notes::homework_view)
+        .on_event('e', events::events_view)
+        .on_event('x', schedules_export)
+        .on_event('s', stats::stats_view)
+        .on_event('a', attendance::attendance_view)
 }
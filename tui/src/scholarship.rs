@@ -0,0 +1,124 @@
+use cursive::{
+    Cursive,
+    theme::{BaseColor, ColorStyle, Effect},
+    utils::markup::StyledString,
+    view::Scrollable,
+    views::{Dialog, TextView},
+};
+use cursive_async_view::AsyncView;
+use lounge_parser::{
+    grades::GradeItem,
+    scholarship::{ScholarshipRule, ScholarshipRules, check_scholarship},
+};
+use tokio::runtime::Runtime;
+
+use crate::{config, credentials};
+
+use rust_i18n::t;
+
+rust_i18n::i18n!();
+
+const RULES_FILE: &str = "scholarship";
+
+pub fn get_rules() -> ScholarshipRules {
+    config::load_data(RULES_FILE).unwrap_or_default()
+}
+
+fn verdict_text(
+    text: &mut StyledString,
+    title: String,
+    semesters: &[Vec<GradeItem>; 8],
+    rule: &ScholarshipRule,
+) {
+    let verdict = check_scholarship(semesters, rule);
+
+    text.append_styled(format!("{}\n", title), Effect::Bold);
+
+    let semester = match verdict.semester {
+        Some(semester) => semester,
+        None => {
+            text.append_plain(format!("{}\n\n", t!("scholarship.no_grades")));
+            return;
+        }
+    };
+
+    if verdict.eligible() {
+        text.append_styled(
+            format!("✓ {}\n\n", t!("scholarship.eligible", n = semester)),
+            ColorStyle::front(BaseColor::Green),
+        );
+        return;
+    }
+
+    text.append_styled(
+        format!("✗ {}\n", t!("scholarship.not_eligible", n = semester)),
+        ColorStyle::front(BaseColor::Red),
+    );
+    for blocker in verdict.blockers {
+        text.append_plain(format!("■ {}\n", blocker.item.name));
+        text.append_styled(
+            format!(
+                "  {} ({}, {})\n",
+                t!("scholarship_reason.".to_owned() + blocker.reason.to_text()),
+                t!("grades_grade.".to_owned() + blocker.item.grade.to_string()),
+                t!("debts.semester", n = blocker.semester)
+            ),
+            ColorStyle::tertiary(),
+        );
+    }
+    text.append_plain("\n");
+}
+
+/// Verdicts for the standard and the increased scholarship by the latest semester
+pub fn scholarship_view(s: &mut Cursive) {
+    credentials::with_pin(s, scholarship_show);
+}
+
+fn scholarship_show(s: &mut Cursive, pin: String) {
+    let cfg = config::get_config().unwrap();
+    let rules = get_rules();
+    let rules_path = config::get_data_path(RULES_FILE)
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
+
+    let async_view = AsyncView::new_with_bg_creator(
+        s,
+        move || {
+            let rt = Runtime::new().unwrap();
+            Ok(rt.block_on(lounge_parser::get_grades(&pin, &cfg.last_name)))
+        },
+        move |result| {
+            let mut text = StyledString::new();
+
+            match result {
+                Ok(semesters) => {
+                    verdict_text(
+                        &mut text,
+                        t!("scholarship.standard").to_string(),
+                        &semesters,
+                        &rules.standard,
+                    );
+                    verdict_text(
+                        &mut text,
+                        t!("scholarship.increased").to_string(),
+                        &semesters,
+                        &rules.increased,
+                    );
+                }
+                Err(err) => text.append_plain(t!("errors.grades", e = err)),
+            }
+
+            text.append_styled(
+                t!("scholarship.rules_file", path = &rules_path),
+                ColorStyle::tertiary(),
+            );
+            TextView::new(text)
+        },
+    );
+
+    s.add_layer(
+        Dialog::around(async_view.with_width(50).scrollable())
+            .title(t!("scholarship.title"))
+            .dismiss_button(t!("actions.close")),
+    );
+}
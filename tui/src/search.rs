@@ -0,0 +1,175 @@
+use chrono::{Months, NaiveTime};
+use cursive::{
+    Cursive,
+    theme::{ColorStyle, Effect, Effects, Style},
+    utils::markup::StyledString,
+    view::{Margins, Nameable, Resizable, Scrollable},
+    views::{Dialog, EditView, LinearLayout, PaddedView, SelectView, TextView},
+};
+use cursive_async_view::AsyncView;
+use lounge_parser::{filters::LessonFilters, search::SearchMatch, search_schedules, timezone};
+use tokio::runtime::Runtime;
+
+use crate::{
+    config,
+    schedules::{
+        lesson_type_place_view, schedules_additional_type_to_text, schedules_lesson_place_str,
+    },
+    state,
+};
+
+use rust_i18n::t;
+
+rust_i18n::i18n!();
+
+fn search_results_view(result: Result<Vec<SearchMatch>, String>) -> LinearLayout {
+    let mut results_list = LinearLayout::vertical();
+
+    match result {
+        Ok(matches) => {
+            let now = timezone::now().naive_local();
+            let mut next_marked = false;
+
+            results_list.add_child(TextView::new(format!(
+                "{}\n\n",
+                t!("search.found", count = matches.len())
+            )));
+
+            for item in matches {
+                let lesson = &item.lesson;
+                let is_past = match NaiveTime::parse_from_str(&lesson.time_start, "%H:%M") {
+                    Ok(time) => item.date.and_time(time) < now,
+                    Err(_) => item.date < now.date(),
+                };
+
+                let mut header = StyledString::new();
+                if !is_past && !next_marked {
+                    header.append_styled(format!("{} ", t!("search.next")), Effect::Bold);
+                    next_marked = true;
+                }
+                header.append_styled(
+                    format!(
+                        "{}, {} {}–{}",
+                        item.week_day,
+                        item.date.format("%d.%m.%Y"),
+                        lesson.time_start,
+                        lesson.time_end
+                    ),
+                    if is_past {
+                        ColorStyle::tertiary()
+                    } else {
+                        ColorStyle::primary()
+                    },
+                );
+
+                let mut lesson_text = StyledString::plain(&lesson.text);
+                if let Some(teacher) = &lesson.additional.teacher_name {
+                    lesson_text.append_plain(" ");
+                    lesson_text.append_styled(
+                        teacher,
+                        Style {
+                            effects: Effects::empty(),
+                            color: ColorStyle::tertiary(),
+                        },
+                    );
+                }
+
+                results_list.add_child(TextView::new(header));
+                results_list.add_child(lesson_type_place_view(
+                    schedules_additional_type_to_text(&lesson.additional.r#type.to_text()),
+                    schedules_lesson_place_str(lesson),
+                ));
+                results_list.add_child(PaddedView::new(
+                    Margins::tb(0, 1),
+                    TextView::new(lesson_text).full_width().max_width(40),
+                ));
+            }
+        }
+        Err(err) => {
+            results_list.add_child(TextView::new(t!("errors.schedules", e = &err)));
+        }
+    }
+
+    results_list
+}
+
+fn search_submit(s: &mut Cursive, query: &str) {
+    if query.trim().is_empty() {
+        return;
+    }
+
+    let months = s
+        .call_on_name("search-range", |view: &mut SelectView<u32>| {
+            view.selection()
+        })
+        .flatten()
+        .map(|months| *months)
+        .unwrap_or(1);
+    let date_from = state::get_state(s).schedules_date;
+    let date_to = date_from + Months::new(months);
+    let cfg = config::get_config().unwrap();
+    // same lessons as in the schedules view
+    let filters = if state::get_state(s).show_hidden {
+        LessonFilters::default()
+    } else {
+        cfg.filters
+    };
+    let title = t!("search.results", query = query).to_string();
+    let query = query.to_string();
+
+    s.pop_layer();
+
+    let async_view = AsyncView::new_with_bg_creator(
+        s,
+        move || {
+            let rt = Runtime::new().unwrap();
+            let search_result = rt.block_on(search_schedules(
+                date_from,
+                date_to,
+                &cfg.group_id,
+                &query,
+                &filters,
+            ));
+
+            Ok(search_result)
+        },
+        search_results_view,
+    );
+
+    s.add_layer(
+        Dialog::around(async_view.with_width(40).scrollable())
+            .title(title)
+            .dismiss_button(t!("actions.close")),
+    );
+}
+
+pub fn search_prompt(s: &mut Cursive) {
+    let query = EditView::new()
+        .on_submit(search_submit)
+        .with_name("search-query")
+        .fixed_width(40);
+    let range = SelectView::<u32>::new()
+        .popup()
+        .item(t!("search.range_month"), 1)
+        .item(t!("search.range_three_months"), 3)
+        .item(t!("search.range_semester"), 6)
+        .with_name("search-range");
+
+    let dialog = Dialog::around(
+        LinearLayout::vertical()
+            .child(TextView::new(t!("search.hint")))
+            .child(query)
+            .child(TextView::new(format!("\n{}", t!("search.range"))))
+            .child(range),
+    )
+    .title(t!("search.title"))
+    .button(t!("search.find"), |s| {
+        let query = s
+            .call_on_name("search-query", |view: &mut EditView| view.get_content())
+            .unwrap();
+        search_submit(s, &query);
+    })
+    .dismiss_button(t!("actions.cancel"));
+
+    s.add_layer(dialog);
+}
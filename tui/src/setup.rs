@@ -1,11 +1,13 @@
-use chrono::Utc;
 use chrono::prelude::*;
+use chrono_tz::Tz;
 use cursive::align::HAlign;
 use cursive::reexports::enumset::__internal::EnumSetTypeRepr;
 use cursive::theme::Theme;
 use cursive::view::Nameable;
+use cursive::view::Resizable;
 use cursive::views::EditView;
 use cursive::views::LinearLayout;
+use cursive::views::{Checkbox, TextArea};
 use cursive::{
     Cursive,
     view::Scrollable,
@@ -13,15 +15,34 @@ use cursive::{
 };
 use cursive_async_view::AsyncView;
 use cursive_calendar_view::{CalendarView, EnglishLocale, ViewMode};
+use lounge_parser::errors::ErrorCode;
+use lounge_parser::filters::LessonFilters;
+use lounge_parser::schedules::additional::LessonType;
+use lounge_parser::timezone::TIMEZONE;
 use tokio::runtime::Runtime;
 
 use crate::config;
+use crate::credentials;
+use crate::events;
+use crate::grades;
 use crate::main_screen;
-use crate::schedules::schedules_view;
+use crate::schedules::{schedules_additional_type_to_text, schedules_reload};
+use crate::state;
 use rust_i18n::t;
 
 rust_i18n::i18n!();
 
+const FILTER_TYPES: [LessonType; 8] = [
+    LessonType::Lecture,
+    LessonType::Practice,
+    LessonType::Consultation,
+    LessonType::Exam,
+    LessonType::SubjectReport,
+    LessonType::SubjectReportWithGrade,
+    LessonType::CourseWorkDefend,
+    LessonType::Meeting,
+];
+
 pub fn load_theme(s: &mut Cursive, theme: &u8) {
     match theme {
         1 => s.load_toml(include_str!("themes/monokai.toml")).unwrap(),
@@ -63,29 +84,33 @@ pub fn select_theme(s: &mut Cursive) {
     s.add_layer(Dialog::around(select.scrollable()).title(t!("prompts.specify_theme")));
 }
 
+// cursive_calendar_view still takes the deprecated chrono::Date, keep it in one place
+#[allow(deprecated)]
+fn calendar_date(date: NaiveDate) -> Date<Tz> {
+    TIMEZONE
+        .from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .unwrap()
+        .date()
+}
+
 pub fn select_date(s: &mut Cursive) {
-    let cfg = config::get_config().unwrap();
+    let date = state::get_state(s).schedules_date;
 
-    let mut calendar = CalendarView::<Utc, EnglishLocale>::new(
-        Utc.timestamp_opt(cfg.selected_date, 0).unwrap().date(),
-    );
+    let mut calendar = CalendarView::<Tz, EnglishLocale>::new(calendar_date(date));
 
     calendar.set_view_mode(ViewMode::Year);
-    calendar.set_earliest_date(Some(Utc.ymd(2023, 1, 1)));
-    calendar.set_latest_date(Some(Utc.ymd(2040, 12, 31)));
+    calendar.set_earliest_date(Some(calendar_date(
+        NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(),
+    )));
+    calendar.set_latest_date(Some(calendar_date(
+        NaiveDate::from_ymd_opt(2040, 12, 31).unwrap(),
+    )));
     calendar.set_show_iso_weeks(true);
 
-    calendar.set_on_submit(move |siv: &mut Cursive, date: &Date<Utc>| {
-        let mut cfg = config::get_config().unwrap();
-        cfg.selected_date = Date::<Utc>::from(*date)
-            .and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap())
-            .unwrap()
-            .timestamp();
-        config::store_config(cfg).unwrap();
+    calendar.set_on_submit(move |siv: &mut Cursive, date| {
+        state::get_state(siv).schedules_date = date.naive_local();
         siv.pop_layer();
-
-        let schedules_view = schedules_view(siv);
-        siv.add_layer(schedules_view);
+        schedules_reload(siv);
     });
 
     s.add_layer(Dialog::around(calendar.with_name("calendar")).title(t!("prompts.specify_date")));
@@ -223,9 +248,102 @@ pub fn group_chooser(s: &mut Cursive, is_setup: bool) {
     s.add_layer(async_view.with_width(40));
 }
 
+/// `None` passphrase means the user opted into plain text storage
+fn grades_credentials_store(
+    s: &mut Cursive,
+    pin: String,
+    last_name: String,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    let mut cfg = config::get_config().map_err(|err| err.to_string())?;
+    cfg.last_name = last_name;
+    cfg.pin_plain = passphrase.is_none() && !pin.is_empty();
+    cfg.pin_encrypted = match &passphrase {
+        Some(passphrase) => Some(credentials::encrypt_pin(&pin, passphrase)?),
+        None => None,
+    };
+    cfg.pin = if cfg.pin_plain { pin.clone() } else { String::new() };
+    config::store_config(cfg).map_err(|err| err.to_string())?;
+
+    // the badge and the cached grades belong to the old credentials
+    grades::grades_badge_reset(s);
+    if pin.is_empty() {
+        credentials::session_lock(s);
+    } else {
+        credentials::session_unlock(s, pin.clone());
+        grades::grades_badge_fetch(s, pin);
+    }
+    Ok(())
+}
+
+fn remove_named_layer(s: &mut Cursive, name: &str) {
+    if let Some(position) = s.screen_mut().find_layer_from_name(name) {
+        s.screen_mut().remove_layer(position);
+    }
+}
+
+/// Saves the PIN only after the grades page has accepted it
+fn grades_verify(s: &mut Cursive, pin: String, last_name: String, passphrase: Option<String>) {
+    // every Apply starts a new check, a late result of an earlier one must not be saved
+    let attempt = {
+        let state = state::get_state(s);
+        state.grades_verify_attempt += 1;
+        state.grades_verify_attempt
+    };
+    let cb_sink = s.cb_sink().clone();
+    let async_view = AsyncView::new_with_bg_creator(
+        s,
+        move || {
+            let rt: Runtime = Runtime::new().unwrap();
+            let result = rt
+                .block_on(lounge_parser::get_grades(&pin, &last_name))
+                .map(|_| ());
+
+            if result.is_ok() {
+                let _ = cb_sink.send(Box::new(move |s: &mut Cursive| {
+                    // closed before the check finished: treat it as cancelled
+                    if state::get_state(s).grades_verify_attempt != attempt
+                        || s.find_name::<Dialog>("grades-verify").is_none()
+                    {
+                        return;
+                    }
+                    remove_named_layer(s, "grades-verify");
+                    match grades_credentials_store(s, pin, last_name, passphrase) {
+                        Ok(()) => {
+                            remove_named_layer(s, "grades-settings");
+                            s.add_layer(Dialog::info(t!("grades_verify.saved")));
+                        }
+                        Err(err) => s.add_layer(Dialog::info(t!("errors.some", e = err))),
+                    }
+                }));
+            }
+
+            Ok(result)
+        },
+        |result| match result {
+            Ok(()) => TextView::new(t!("grades_verify.saved")),
+            Err(err) if err == ErrorCode::DataMismatchError.get_description() => {
+                TextView::new(t!("grades_verify.mismatch"))
+            }
+            Err(err) => TextView::new(t!("grades_verify.failed", e = err)),
+        },
+    );
+
+    s.add_layer(
+        Dialog::around(async_view.with_width(40))
+            .title(t!("grades_verify.title"))
+            .dismiss_button(t!("actions.close"))
+            .with_name("grades-verify"),
+    );
+}
+
 pub fn grades_settings(s: &mut Cursive) {
     let cfg = config::get_config().unwrap();
-    let pin = EditView::new().content(cfg.pin).with_name("pin-input");
+    // an encrypted PIN is never shown, it has to be typed again to change the settings
+    let pin = EditView::new()
+        .secret()
+        .content(credentials::stored_pin(&cfg).unwrap_or_default())
+        .with_name("pin-input");
     let last_name = EditView::new()
         .content(cfg.last_name)
         .with_name("last_name_input");
@@ -236,18 +354,245 @@ pub fn grades_settings(s: &mut Cursive) {
             .child(TextView::new(t!("prompts.enter_pin")))
             .child(pin)
             .child(TextView::new(t!("prompts.enter_last_name")))
-            .child(last_name),
+            .child(last_name)
+            .child(TextView::new(format!("\n{}", t!("credentials.passphrase"))))
+            .child(EditView::new().secret().with_name("passphrase-input"))
+            .child(TextView::new(t!("credentials.passphrase_repeat")))
+            .child(EditView::new().secret().with_name("passphrase-repeat"))
+            .child(
+                LinearLayout::horizontal()
+                    .child(
+                        Checkbox::new()
+                            .with_checked(cfg.pin_plain)
+                            .with_name("pin-plain"),
+                    )
+                    .child(TextView::new(format!(" {}", t!("credentials.store_plain")))),
+            ),
     )
-    .button("Применить", |s| {
-        let pin = s.find_name::<EditView>("pin-input").unwrap();
-        let last_name = s.find_name::<EditView>("last_name_input").unwrap();
+    .button(t!("actions.apply"), |s| {
+        let [pin, last_name, passphrase, repeat] = [
+            "pin-input",
+            "last_name_input",
+            "passphrase-input",
+            "passphrase-repeat",
+        ]
+        .map(|name| {
+            s.call_on_name(name, |view: &mut EditView| {
+                view.get_content().trim().to_string()
+            })
+            .unwrap_or_default()
+        });
+        let plain = s
+            .call_on_name("pin-plain", |view: &mut Checkbox| view.is_checked())
+            .unwrap_or(false);
+
+        // both fields cleared: grades are turned off, nothing to verify
+        if pin.is_empty() && last_name.is_empty() {
+            match grades_credentials_store(s, pin, last_name, None) {
+                Ok(()) => {
+                    s.pop_layer();
+                }
+                Err(err) => s.add_layer(Dialog::info(t!("errors.some", e = err))),
+            }
+            return;
+        }
+        if pin.is_empty() || last_name.is_empty() {
+            s.add_layer(Dialog::info(t!("grades_verify.incomplete")));
+            return;
+        }
+
+        let passphrase = if plain {
+            None
+        } else {
+            if let Err(err) = credentials::check_passphrase(&passphrase, &repeat) {
+                s.add_layer(Dialog::info(err));
+                return;
+            }
+            Some(passphrase)
+        };
+
+        grades_verify(s, pin, last_name, passphrase);
+    })
+    .dismiss_button(t!("actions.cancel"))
+    .title(t!("grades_setup"))
+    .with_name("grades-settings");
+    s.add_layer(dialog);
+}
+
+pub fn lesson_filters_settings(s: &mut Cursive) {
+    let cfg = config::get_config().unwrap();
+
+    let subjects = TextArea::new()
+        .content(cfg.filters.hidden_subjects().join("\n"))
+        .with_name("filters-subjects")
+        .min_height(3);
+
+    let mut types = LinearLayout::vertical();
+    for r#type in FILTER_TYPES {
+        let key = r#type.to_text();
+        types.add_child(
+            LinearLayout::horizontal()
+                .child(
+                    Checkbox::new()
+                        .with_checked(cfg.filters.hidden_types().contains(&key))
+                        .with_name(format!("filters-type-{}", key)),
+                )
+                .child(TextView::new(format!(
+                    " {}",
+                    schedules_additional_type_to_text(&key)
+                ))),
+        );
+    }
+
+    let subgroup = SelectView::<u8>::new()
+        .popup()
+        .item(t!("filters.all_subgroups"), 0)
+        .item("1", 1)
+        .item("2", 2)
+        .item("3", 3)
+        .selected(cfg.filters.subgroup().unwrap_or(0) as usize)
+        .with_name("filters-subgroup");
+
+    let dialog = Dialog::around(
+        LinearLayout::vertical()
+            .child(TextView::new(t!("filters.hidden_subjects")))
+            .child(subjects)
+            .child(TextView::new(format!("\n{}", t!("filters.hidden_types"))))
+            .child(types)
+            .child(TextView::new(format!("\n{}", t!("filters.subgroup"))))
+            .child(subgroup)
+            .fixed_width(40),
+    )
+    .button(t!("actions.apply"), |s| {
+        let hidden_subjects = s
+            .call_on_name("filters-subjects", |view: &mut TextArea| {
+                view.get_content()
+                    .lines()
+                    .map(|line| line.trim().to_string())
+                    .filter(|line| !line.is_empty())
+                    .collect::<Vec<String>>()
+            })
+            .unwrap();
+        let hidden_types = FILTER_TYPES
+            .iter()
+            .map(|r#type| r#type.to_text())
+            .filter(|key| {
+                s.call_on_name(&format!("filters-type-{}", key), |view: &mut Checkbox| {
+                    view.is_checked()
+                })
+                .unwrap_or(false)
+            })
+            .collect::<Vec<String>>();
+        let subgroup = s
+            .call_on_name("filters-subgroup", |view: &mut SelectView<u8>| {
+                view.selection()
+            })
+            .flatten()
+            .map(|subgroup| *subgroup)
+            .filter(|subgroup| *subgroup > 0);
+
+        let mut cfg = config::get_config().unwrap();
+        cfg.filters = LessonFilters::new(hidden_subjects, hidden_types, subgroup);
+        config::store_config(cfg).unwrap();
+        s.pop_layer();
+    })
+    .dismiss_button(t!("actions.cancel"))
+    .title(t!("filters.title"));
+
+    s.add_layer(dialog.scrollable());
+}
+
+pub fn overlay_calendars_settings(s: &mut Cursive) {
+    let cfg = config::get_config().unwrap();
+
+    let paths = TextArea::new()
+        .content(cfg.overlay_calendars.join("\n"))
+        .with_name("overlay-paths")
+        .min_height(3);
+
+    let dialog = Dialog::around(
+        LinearLayout::vertical()
+            .child(TextView::new(t!("overlays.paths")))
+            .child(paths)
+            .fixed_width(50),
+    )
+    .button(t!("actions.apply"), |s| {
+        let paths = s
+            .call_on_name("overlay-paths", |view: &mut TextArea| {
+                view.get_content()
+                    .lines()
+                    .map(|line| line.trim().to_string())
+                    .filter(|line| !line.is_empty())
+                    .collect::<Vec<String>>()
+            })
+            .unwrap();
+
+        // tell right away which files can't be read instead of silently showing nothing
+        let report = paths
+            .iter()
+            .map(|path| match events::read_overlay(path) {
+                Ok(overlay) if overlay.skipped.is_empty() => {
+                    t!("overlays.loaded", path = path, count = overlay.events.len())
+                }
+                Ok(overlay) => t!(
+                    "overlays.skipped",
+                    path = path,
+                    count = overlay.events.len(),
+                    skipped = overlay.skipped.join(", ")
+                ),
+                Err(err) => t!("overlays.failed", path = path, e = err),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
         let mut cfg = config::get_config().unwrap();
-        cfg.pin = pin.get_content().to_string();
-        cfg.last_name = last_name.get_content().to_string();
+        cfg.overlay_calendars = paths;
         config::store_config(cfg).unwrap();
         s.pop_layer();
+
+        if !report.is_empty() {
+            s.add_layer(Dialog::info(report).title(t!("overlays.title")));
+        }
     })
     .dismiss_button(t!("actions.cancel"))
-    .title(t!("grades_setup"));
+    .title(t!("overlays.title"));
+
+    s.add_layer(dialog);
+}
+
+pub fn absence_limit_settings(s: &mut Cursive) {
+    let cfg = config::get_config().unwrap();
+
+    let dialog = Dialog::around(
+        LinearLayout::vertical()
+            .child(TextView::new(t!("attendance.limit_hint")))
+            .child(
+                EditView::new()
+                    .content(cfg.absence_limit.to_string())
+                    .with_name("absence-limit")
+                    .fixed_width(6),
+            )
+            .fixed_width(40),
+    )
+    .button(t!("actions.apply"), |s| {
+        let limit = s
+            .call_on_name("absence-limit", |view: &mut EditView| {
+                view.get_content().trim().parse::<u8>()
+            })
+            .unwrap();
+
+        match limit {
+            Ok(limit) => {
+                let mut cfg = config::get_config().unwrap();
+                cfg.absence_limit = limit;
+                config::store_config(cfg).unwrap();
+                s.pop_layer();
+            }
+            Err(_) => s.add_layer(Dialog::info(t!("attendance.limit_invalid"))),
+        }
+    })
+    .dismiss_button(t!("actions.cancel"))
+    .title(t!("actions.specify_absence_limit"));
+
     s.add_layer(dialog);
 }
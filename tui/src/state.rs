@@ -0,0 +1,48 @@
+use std::{sync::Arc, time::Instant};
+
+use chrono::NaiveDate;
+use cursive::Cursive;
+use lounge_parser::{grades::GradeItem, timezone};
+
+use crate::grades::{GradesFilter, GradesSort};
+
+/// Session state kept in memory only, so that navigating doesn't rewrite the config file
+pub struct LoungeState {
+    pub schedules_date: NaiveDate,
+    pub show_hidden: bool,
+    /// Academic debts count shown in the menubar, `None` until grades are loaded
+    pub debts: Option<usize>,
+    pub grades_sort: GradesSort,
+    pub grades_filter: GradesFilter,
+    /// Last fetched grades, used to mark exams and credits in the schedules
+    pub grades: Option<Arc<[Vec<GradeItem>; 8]>>,
+    /// Decrypted PIN, see `credentials::with_pin`
+    pub pin: Option<String>,
+    pub pin_used_at: Option<Instant>,
+    /// Increased on every grades settings check, see `setup::grades_verify`
+    pub grades_verify_attempt: u64,
+}
+
+impl ::std::default::Default for LoungeState {
+    fn default() -> Self {
+        Self {
+            schedules_date: timezone::today(),
+            show_hidden: false,
+            debts: None,
+            grades_sort: GradesSort::default(),
+            grades_filter: GradesFilter::default(),
+            grades: None,
+            pin: None,
+            pin_used_at: None,
+            grades_verify_attempt: 0,
+        }
+    }
+}
+
+pub fn get_state(s: &mut Cursive) -> &mut LoungeState {
+    if s.user_data::<LoungeState>().is_none() {
+        s.set_user_data(LoungeState::default());
+    }
+
+    s.user_data::<LoungeState>().unwrap()
+}
@@ -0,0 +1,167 @@
+use cursive::{
+    Cursive,
+    theme::{ColorStyle, Effect},
+    utils::markup::StyledString,
+    view::Scrollable,
+    views::{Button, Dialog, LinearLayout, TextView},
+};
+use cursive_async_view::AsyncView;
+use lounge_parser::{
+    get_schedules,
+    stats::{SubjectStats, semester_bounds, workload_stats, workload_to_csv},
+    timezone,
+};
+use tokio::runtime::Runtime;
+
+use crate::{config, schedules::schedules_additional_type_to_text, state};
+
+use rust_i18n::t;
+
+rust_i18n::i18n!();
+
+const TYPE_WIDTH: usize = 16;
+
+fn hours_str(minutes: i64) -> String {
+    t!("stats.hours", h = format!("{:.1}", minutes as f64 / 60.0)).to_string()
+}
+
+fn pad(text: &str, width: usize) -> String {
+    let length = text.chars().count();
+    if length >= width {
+        text.chars().take(width).collect()
+    } else {
+        format!("{}{}", text, " ".repeat(width - length))
+    }
+}
+
+fn stats_export(s: &mut Cursive, csv: &str, file_name: &str) {
+    match config::write_file(file_name, csv) {
+        Ok(path) => s.add_layer(Dialog::info(t!(
+            "export.saved",
+            path = path.display().to_string()
+        ))),
+        Err(err) => s.add_layer(Dialog::info(t!("errors.some", e = err))),
+    }
+}
+
+fn stats_table_view(result: Result<Vec<SubjectStats>, String>, file_name: String) -> LinearLayout {
+    let mut table = LinearLayout::vertical();
+
+    let stats = match result {
+        Ok(stats) => stats,
+        Err(err) => {
+            table.add_child(TextView::new(t!("errors.schedules", e = &err)));
+            return table;
+        }
+    };
+
+    if stats.is_empty() {
+        table.add_child(TextView::new(t!("stats.empty")));
+        return table;
+    }
+
+    let mut header = StyledString::new();
+    header.append_styled(
+        format!(
+            "{}{:>7}{:>7}{:>8}{:>8}\n",
+            pad("", TYPE_WIDTH),
+            t!("stats.total"),
+            t!("stats.past"),
+            t!("stats.left"),
+            t!("stats.time")
+        ),
+        ColorStyle::tertiary(),
+    );
+    table.add_child(TextView::new(header).no_wrap());
+
+    for subject in &stats {
+        let mut row = StyledString::new();
+        row.append_styled(&subject.subject, Effect::Bold);
+        if !subject.teachers.is_empty() {
+            row.append_plain(" ");
+            row.append_styled(subject.teachers.join(", "), ColorStyle::tertiary());
+        }
+        row.append_plain("\n");
+
+        for item in &subject.types {
+            row.append_plain(format!(
+                "{}{:>7}{:>7}{:>8}{:>8}\n",
+                pad(
+                    &format!(
+                        "  {}",
+                        schedules_additional_type_to_text(&item.r#type.to_text())
+                    ),
+                    TYPE_WIDTH
+                ),
+                item.total,
+                item.past,
+                item.total - item.past,
+                hours_str(item.minutes)
+            ));
+        }
+
+        if subject.types.len() > 1 {
+            row.append_styled(
+                format!(
+                    "{}{:>7}{:>7}{:>8}{:>8}\n",
+                    pad(&format!("  {}", t!("stats.all_types")), TYPE_WIDTH),
+                    subject.total(),
+                    subject.past(),
+                    subject.remaining(),
+                    hours_str(subject.minutes())
+                ),
+                Effect::Italic,
+            );
+        }
+
+        table.add_child(TextView::new(row).no_wrap());
+    }
+
+    let csv = workload_to_csv(&stats);
+    table.add_child(Button::new(t!("stats.export_csv"), move |s| {
+        stats_export(s, &csv, &file_name)
+    }));
+
+    table
+}
+
+/// Semester around the selected schedules date, aggregated by subject and lesson type
+pub fn stats_view(s: &mut Cursive) {
+    let cfg = config::get_config().unwrap();
+    let (date_from, date_to) = semester_bounds(state::get_state(s).schedules_date);
+    let file_name = format!(
+        "workload-{}-{}.csv",
+        date_from.format("%Y%m%d"),
+        date_to.format("%Y%m%d")
+    );
+
+    let async_view = AsyncView::new_with_bg_creator(
+        s,
+        move || {
+            let rt = Runtime::new().unwrap();
+            let result = rt
+                .block_on(get_schedules(
+                    &date_from.format("%d.%m.%Y").to_string(),
+                    &date_to.format("%d.%m.%Y").to_string(),
+                    &cfg.group_id,
+                ))
+                .map(|mut days| {
+                    cfg.filters.apply(&mut days);
+                    workload_stats(&days, date_from, timezone::now().naive_local())
+                });
+
+            Ok(result)
+        },
+        move |result| stats_table_view(result, file_name.clone()),
+    );
+
+    s.add_layer(
+        Dialog::around(async_view.with_width(60).scrollable())
+            .title(t!(
+                "stats.title",
+                from = date_from.format("%d.%m.%Y"),
+                to = date_to.format("%d.%m.%Y")
+            ))
+            .dismiss_button(t!("actions.close")),
+    );
+}
@@ -0,0 +1,193 @@
+use chrono::NaiveDate;
+use cursive::{
+    theme::{BaseColor, ColorStyle},
+    utils::markup::StyledString,
+    view::Resizable,
+    views::{LinearLayout, TextView},
+};
+use lounge_parser::{
+    events::day_conflicts,
+    schedules::{BellSlot, DayItem, LessonItem},
+};
+
+use crate::{
+    notes::LessonNotes,
+    schedules::{schedules_lesson_place_str, schedules_type_color},
+};
+
+use rust_i18n::t;
+
+rust_i18n::i18n!();
+
+const TIMES_WIDTH: usize = 6;
+const MIN_CELL_WIDTH: usize = 10;
+const MAX_CELL_WIDTH: usize = 24;
+
+/// Picks a cell width so that up to a week of columns fits into the screen.
+/// Returns `(cell_width, total_width)`.
+pub fn timetable_width(screen_width: usize, days: usize) -> (usize, usize) {
+    // dialog borders and paddings
+    let available = screen_width.saturating_sub(TIMES_WIDTH + 6);
+    let columns = days.clamp(1, 7);
+    let cell_width = (available / columns)
+        .saturating_sub(1)
+        .clamp(MIN_CELL_WIDTH, MAX_CELL_WIDTH);
+
+    (cell_width, TIMES_WIDTH + days.max(1) * (cell_width + 1))
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+
+    let mut result: String = text.chars().take(width.saturating_sub(1)).collect();
+    result.push('…');
+    result
+}
+
+// "Теория вероятностей и математическая статистика" -> "ТВиМС"
+fn subject_abbreviation(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+
+    let abbreviation: String = text
+        .split_whitespace()
+        .filter_map(|word| {
+            let first = word.chars().find(|c| c.is_alphanumeric())?;
+            if word.chars().count() <= 2 {
+                Some(first.to_lowercase().collect::<String>())
+            } else {
+                Some(first.to_uppercase().collect::<String>())
+            }
+        })
+        .collect();
+
+    if abbreviation.chars().count() > 1 {
+        truncate(&abbreviation, width)
+    } else {
+        truncate(text, width)
+    }
+}
+
+fn timetable_cell(
+    lessons: &[(&LessonItem, bool)],
+    cell_width: usize,
+    notes: &LessonNotes,
+    date: Option<NaiveDate>,
+) -> TextView {
+    let mut cell = StyledString::new();
+
+    for (index, (lesson, is_conflict)) in lessons.iter().enumerate() {
+        if index > 0 {
+            cell.append_plain("\n");
+        }
+
+        let lesson_type =
+            t!("schedules_type_short.".to_owned() + &lesson.additional.r#type.to_text())
+                .to_string();
+        let has_note = date.is_some_and(|date| notes.get(&lesson.key(date)).is_some());
+        let markers = if has_note { 2 } else { 0 } + if *is_conflict { 2 } else { 0 };
+        let subject_width = cell_width.saturating_sub(lesson_type.chars().count() + 1 + markers);
+
+        if *is_conflict {
+            cell.append_styled("! ", ColorStyle::front(BaseColor::Red));
+        }
+        if has_note {
+            cell.append_styled("✎ ", ColorStyle::front(BaseColor::Yellow));
+        }
+        cell.append_styled(
+            &lesson_type,
+            schedules_type_color(&lesson.additional.r#type),
+        );
+        cell.append_plain(format!(
+            " {}\n",
+            subject_abbreviation(&lesson.text, subject_width)
+        ));
+        cell.append_styled(
+            truncate(&schedules_lesson_place_str(lesson), cell_width),
+            ColorStyle::tertiary(),
+        );
+    }
+
+    TextView::new(cell).no_wrap()
+}
+
+fn timetable_slot_view(slot: &BellSlot) -> TextView {
+    let mut times = StyledString::plain(format!("{}\n", slot.time_start));
+    times.append_styled(&slot.time_end, ColorStyle::tertiary());
+    TextView::new(times).no_wrap()
+}
+
+/// Days as columns, bell slots as rows.
+pub fn timetable_view(
+    result: Result<Vec<DayItem>, String>,
+    cell_width: usize,
+    notes: &LessonNotes,
+    date_from: NaiveDate,
+) -> LinearLayout {
+    let mut timetable = LinearLayout::vertical();
+
+    let schedules = match result {
+        Ok(schedules) => schedules,
+        Err(err) => {
+            timetable.add_child(TextView::new(t!("errors.schedules", e = &err)));
+            return timetable;
+        }
+    };
+
+    let mut header = LinearLayout::horizontal().child(TextView::new("").fixed_width(TIMES_WIDTH));
+    for day in &schedules {
+        header.add_child(
+            TextView::new(truncate(
+                &format!("{}, {}.{}", day.week_day, day.day, day.month),
+                cell_width,
+            ))
+            .no_wrap()
+            .fixed_width(cell_width + 1),
+        );
+    }
+    timetable.add_child(header);
+    timetable.add_child(TextView::new(" "));
+
+    let slots = schedules
+        .iter()
+        .map(|day| &day.slots)
+        .max_by_key(|slots| slots.len());
+
+    let conflicts: Vec<Vec<bool>> = schedules.iter().map(day_conflicts).collect();
+
+    if let Some(slots) = slots {
+        for (index, slot) in slots.iter().enumerate() {
+            // evening slots are usually empty for the whole week
+            if !schedules
+                .iter()
+                .any(|day| day.lessons.iter().any(|lesson| lesson.slot == index))
+            {
+                continue;
+            }
+
+            let mut row = LinearLayout::horizontal()
+                .child(timetable_slot_view(slot).fixed_width(TIMES_WIDTH));
+
+            for (day, conflicts) in schedules.iter().zip(&conflicts) {
+                let lessons: Vec<(&LessonItem, bool)> = day
+                    .lessons
+                    .iter()
+                    .zip(conflicts.iter().copied())
+                    .filter(|(lesson, _)| lesson.slot == index)
+                    .collect();
+                row.add_child(
+                    timetable_cell(&lessons, cell_width, notes, day.date(date_from))
+                        .fixed_width(cell_width + 1),
+                );
+            }
+
+            timetable.add_child(row);
+            timetable.add_child(TextView::new(" "));
+        }
+    }
+
+    timetable
+}